@@ -1 +1 @@
-
+pub mod random;
@@ -0,0 +1,54 @@
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+    fn next_u128(&mut self) -> u128 {
+        ((self.next_u64() as u128) << 64) | self.next_u64() as u128
+    }
+    fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "bound must be positive");
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+    fn below_u128(&mut self, bound: u128) -> u128 {
+        if bound <= u64::MAX as u128 {
+            return self.below(bound as u64) as u128;
+        }
+        let zone = u128::MAX - u128::MAX % bound;
+        loop {
+            let value = self.next_u128();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+impl<R> Rng for &mut R
+where
+    R: Rng + ?Sized,
+{
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SplitMix64 {
+    state: u64,
+}
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+}
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
@@ -0,0 +1,50 @@
+use milans_rust_core::random::{Rng, SplitMix64};
+use milans_rust_library::math::primality::{
+    MillerRabinTest, is_prime_batch, is_prime_batch_bitset,
+};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+const ROUNDS: u32 = 20;
+fn time<R>(name: &str, mut run: impl FnMut() -> R) {
+    black_box(run());
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        black_box(run());
+        best = best.min(start.elapsed());
+    }
+    println!("{name:<40} {best:>12.3?}");
+}
+fn compare(label: &str, values: &[u64]) {
+    println!("{label}: {} values", values.len());
+    time("  iter().map(miller_rabin_test)", || {
+        values
+            .iter()
+            .map(|value| value.miller_rabin_test())
+            .collect::<Vec<_>>()
+    });
+    time("  is_prime_batch", || is_prime_batch(black_box(values)));
+    time("  is_prime_batch_bitset", || {
+        is_prime_batch_bitset(black_box(values))
+    });
+}
+fn main() {
+    let mut rng = SplitMix64::new(25);
+    let count = 1 << 16;
+    let odd_32: Vec<u64> = (0..count).map(|_| rng.next_u64() >> 32 | 1).collect();
+    let odd_64: Vec<u64> = (0..count).map(|_| rng.next_u64() | 1 << 63 | 1).collect();
+    // Values that survive trial division spend all their time in the Miller-Rabin rounds.
+    let primes_64: Vec<u64> = (0..count / 16)
+        .map(|_| {
+            loop {
+                let value = rng.next_u64() | 1 << 63 | 1;
+                if value.miller_rabin_test() {
+                    break value;
+                }
+            }
+        })
+        .collect();
+    compare("odd 32-bit", &odd_32);
+    compare("odd 64-bit", &odd_64);
+    compare("64-bit primes", &primes_64);
+}
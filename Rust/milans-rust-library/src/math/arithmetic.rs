@@ -0,0 +1,166 @@
+use super::factor::{Factorization, Factorize};
+use num::Integer;
+use num_bigint::BigUint;
+// Signed inputs are evaluated at |n| and n = 0 panics. The integer impls factorize on every
+// call, so to evaluate several functions of one n, factorize it once and use the
+// Factorization impl.
+pub trait ArithmeticFunctions {
+    type Value;
+    fn euler_totient(&self) -> Self::Value;
+    fn carmichael_lambda(&self) -> Self::Value;
+    fn mobius(&self) -> i8;
+    fn divisor_sigma(&self, k: u32) -> Self::Value;
+    fn divisor_count(&self) -> Self::Value;
+    fn distinct_prime_factors(&self) -> u32;
+    fn prime_factor_count(&self) -> u32;
+}
+fn assert_nonzero<T: Ord>(factorization: &Factorization<T>) {
+    assert!(!factorization.is_zero(), "arithmetic functions are undefined at 0");
+}
+impl<T> ArithmeticFunctions for Factorization<T>
+where
+    T: Clone + Integer,
+{
+    type Value = T;
+    fn euler_totient(&self) -> T {
+        assert_nonzero(self);
+        self.iter().fold(T::one(), |totient, (prime, exponent)| {
+            totient * (prime.clone() - T::one()) * num::pow(prime.clone(), *exponent as usize - 1)
+        })
+    }
+    fn carmichael_lambda(&self) -> T {
+        assert_nonzero(self);
+        let two = T::one() + T::one();
+        self.iter().fold(T::one(), |lambda, (prime, exponent)| {
+            let value = if *prime == two && *exponent > 2 {
+                num::pow(two.clone(), *exponent as usize - 2)
+            } else {
+                (prime.clone() - T::one()) * num::pow(prime.clone(), *exponent as usize - 1)
+            };
+            lambda.lcm(&value)
+        })
+    }
+    fn mobius(&self) -> i8 {
+        assert_nonzero(self);
+        if self.iter().any(|(_, exponent)| *exponent > 1) {
+            0
+        } else if self.len().is_multiple_of(2) {
+            1
+        } else {
+            -1
+        }
+    }
+    fn divisor_sigma(&self, k: u32) -> T {
+        assert_nonzero(self);
+        self.iter().fold(T::one(), |sigma, (prime, exponent)| {
+            let power = num::pow(prime.clone(), k as usize);
+            let (mut sum, mut term) = (T::one(), T::one());
+            for _ in 0..*exponent {
+                term = term * power.clone();
+                sum = sum + term.clone();
+            }
+            sigma * sum
+        })
+    }
+    fn divisor_count(&self) -> T {
+        assert_nonzero(self);
+        self.iter().fold(T::one(), |count, (_, exponent)| {
+            let mut factor = T::one();
+            for _ in 0..*exponent {
+                factor = factor + T::one();
+            }
+            count * factor
+        })
+    }
+    fn distinct_prime_factors(&self) -> u32 {
+        assert_nonzero(self);
+        self.len() as u32
+    }
+    fn prime_factor_count(&self) -> u32 {
+        assert_nonzero(self);
+        self.iter().map(|(_, exponent)| exponent).sum()
+    }
+}
+macro_rules! arithmetic_functions {
+    ($type:ty) => {
+        impl ArithmeticFunctions for $type {
+            type Value = $type;
+            fn euler_totient(&self) -> Self {
+                self.clone().factorize().euler_totient()
+            }
+            fn carmichael_lambda(&self) -> Self {
+                self.clone().factorize().carmichael_lambda()
+            }
+            fn mobius(&self) -> i8 {
+                self.clone().factorize().mobius()
+            }
+            fn divisor_sigma(&self, k: u32) -> Self {
+                self.clone().factorize().divisor_sigma(k)
+            }
+            fn divisor_count(&self) -> Self {
+                self.clone().factorize().divisor_count()
+            }
+            fn distinct_prime_factors(&self) -> u32 {
+                self.clone().factorize().distinct_prime_factors()
+            }
+            fn prime_factor_count(&self) -> u32 {
+                self.clone().factorize().prime_factor_count()
+            }
+        }
+    };
+    ($($type:ty),+) => {
+        $(arithmetic_functions!($type);)+
+    };
+}
+arithmetic_functions!(
+    u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, BigUint
+);
+pub fn totient_table(n: usize) -> Vec<u64> {
+    let mut totient = vec![0; n + 1];
+    let mut primes = Vec::new();
+    if n >= 1 {
+        totient[1] = 1;
+    }
+    for i in 2..=n {
+        if totient[i] == 0 {
+            totient[i] = i as u64 - 1;
+            primes.push(i);
+        }
+        for &prime in &primes {
+            if i * prime > n {
+                break;
+            }
+            if i % prime == 0 {
+                totient[i * prime] = totient[i] * prime as u64;
+                break;
+            }
+            totient[i * prime] = totient[i] * (prime as u64 - 1);
+        }
+    }
+    totient
+}
+pub fn mobius_table(n: usize) -> Vec<i8> {
+    let mut mobius = vec![0; n + 1];
+    let mut composite = vec![false; n + 1];
+    let mut primes = Vec::new();
+    if n >= 1 {
+        mobius[1] = 1;
+    }
+    for i in 2..=n {
+        if !composite[i] {
+            mobius[i] = -1;
+            primes.push(i);
+        }
+        for &prime in &primes {
+            if i * prime > n {
+                break;
+            }
+            composite[i * prime] = true;
+            if i % prime == 0 {
+                break;
+            }
+            mobius[i * prime] = -mobius[i];
+        }
+    }
+    mobius
+}
@@ -0,0 +1,442 @@
+use super::factor::Factorize;
+use super::factor::ecm::{ECM_SCHEDULE, ecm_find_factor};
+use super::modular::JacobiSymbol;
+use super::primality::MillerRabinTest;
+use super::sieve::Primes;
+use milans_rust_core::random::SplitMix64;
+use num::{Integer, One, ToPrimitive, Zero};
+use num_bigint::{BigInt, BigUint};
+use std::fmt::{self, Display, Formatter};
+use std::str::{FromStr, Lines};
+const TRIAL_DIVISION_BITS: u64 = 32;
+const SMOOTH_BOUND: u64 = 1 << 16;
+const WITNESS_ATTEMPTS: u32 = 1000;
+const ECM_LEVELS: usize = 2;
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CertifiedFactor {
+    pub prime: BigUint,
+    pub exponent: u32,
+    pub witness: BigUint,
+    pub certificate: PrimalityCertificate,
+}
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PrimalityCertificate {
+    TrialDivision {
+        prime: BigUint,
+    },
+    Pratt {
+        prime: BigUint,
+        factors: Vec<CertifiedFactor>,
+    },
+    Pocklington {
+        prime: BigUint,
+        factors: Vec<CertifiedFactor>,
+    },
+    BlsMinus {
+        prime: BigUint,
+        factors: Vec<CertifiedFactor>,
+    },
+    BlsPlus {
+        prime: BigUint,
+        discriminant: BigUint,
+        factors: Vec<CertifiedFactor>,
+    },
+}
+fn partial_factorization(m: &BigUint, n: &BigUint) -> (Vec<(BigUint, u32)>, BigUint) {
+    let mut factors = Vec::new();
+    let mut rest = m.clone();
+    let mut divide = |rest: &mut BigUint, prime: BigUint| {
+        let mut exponent = 0;
+        while (&*rest % &prime).is_zero() {
+            *rest /= &prime;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((prime, exponent));
+        }
+    };
+    for prime in Primes::new().take_while(|&prime| prime < SMOOTH_BOUND) {
+        divide(&mut rest, BigUint::from(prime));
+    }
+    let mut rng = SplitMix64::new((&rest % u64::MAX).to_u64().unwrap());
+    while !rest.is_one() {
+        if (&rest).miller_rabin_test() {
+            let prime = rest.clone();
+            divide(&mut rest, prime);
+            break;
+        }
+        if let Some(small) = rest.to_u128() {
+            for (prime, _) in small.factorize() {
+                divide(&mut rest, BigUint::from(prime));
+            }
+            break;
+        }
+        if (m / &rest).pow(2) > *n {
+            break;
+        }
+        let Some(divisor) = ECM_SCHEDULE[..ECM_LEVELS]
+            .iter()
+            .find_map(|&(_, b1, curves)| ecm_find_factor(&rest, b1, 100 * b1, curves, &mut rng))
+        else {
+            break;
+        };
+        for (prime, _) in divisor.factorize() {
+            divide(&mut rest, prime);
+        }
+    }
+    (factors, rest)
+}
+fn full_factorization(m: &BigUint) -> Vec<(BigUint, u32)> {
+    m.clone().factorize().into_iter().collect()
+}
+fn lucas_u_test(n: &BigUint, p: &BigUint, q: &BigUint, k: &BigUint) -> BigUint {
+    let subtract = |a: BigUint, b: BigUint| (a % n + n - b % n) % n;
+    let (mut v, mut next, mut power) = (BigUint::from(2u32) % n, p % n, BigUint::one());
+    for bit in (0..k.bits()).rev() {
+        let middle = subtract(&v * &next, p * &power);
+        if k.bit(bit) {
+            next = subtract(&next * &next, &power * q * 2u32);
+            v = middle;
+            power = &power * &power % n * q % n;
+        } else {
+            v = subtract(&v * &v, &power * 2u32);
+            next = middle;
+            power = &power * &power % n;
+        }
+    }
+    subtract(next * 2u32, p * v)
+}
+fn lucas_parameter(n: &BigUint, discriminant: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let four = BigInt::from(4);
+    let numerator = BigInt::from(p * p) - BigInt::from(discriminant.clone());
+    if !(&numerator % &four).is_zero() {
+        return None;
+    }
+    let q = (numerator / four).mod_floor(&BigInt::from(n.clone()));
+    let q = q.magnitude().clone();
+    (!q.is_zero() && q.gcd(n).is_one()).then_some(q)
+}
+fn certify_factors<F>(factors: &[(BigUint, u32)], mut witness: F) -> Option<Vec<CertifiedFactor>>
+where
+    F: FnMut(&BigUint) -> Option<BigUint>,
+{
+    factors
+        .iter()
+        .map(|(prime, exponent)| {
+            Some(CertifiedFactor {
+                prime: prime.clone(),
+                exponent: *exponent,
+                witness: witness(prime)?,
+                certificate: PrimalityCertificate::generate(prime)?,
+            })
+        })
+        .collect()
+}
+fn minus_witness(n: &BigUint, prime: &BigUint, strict: bool) -> Option<BigUint> {
+    let minus = n - 1u32;
+    (2..WITNESS_ATTEMPTS + 2).map(BigUint::from).find(|a| {
+        let power = a.modpow(&(&minus / prime), n);
+        a.modpow(&minus, n).is_one()
+            && if strict {
+                !power.is_one()
+            } else {
+                (power + &minus).gcd(n).is_one()
+            }
+    })
+}
+fn trial_division(n: &BigUint) -> bool {
+    let n = n.to_u64().unwrap();
+    n >= 2
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+fn product(factors: &[(BigUint, u32)]) -> BigUint {
+    factors
+        .iter()
+        .map(|(prime, exponent)| prime.pow(*exponent))
+        .product()
+}
+impl PrimalityCertificate {
+    pub fn generate(n: &BigUint) -> Option<Self> {
+        if n.bits() <= TRIAL_DIVISION_BITS {
+            return trial_division(n)
+                .then(|| PrimalityCertificate::TrialDivision { prime: n.clone() });
+        }
+        if n.is_even() || !n.miller_rabin_test() {
+            return None;
+        }
+        let minus = n - 1u32;
+        let (factors, rest) = partial_factorization(&minus, n);
+        let smooth = product(&factors);
+        if rest.is_one() {
+            return Some(PrimalityCertificate::Pratt {
+                prime: n.clone(),
+                factors: certify_factors(&factors, |q| minus_witness(n, q, true))?,
+            });
+        }
+        if &smooth * &smooth > *n {
+            return Some(PrimalityCertificate::Pocklington {
+                prime: n.clone(),
+                factors: certify_factors(&factors, |q| minus_witness(n, q, false))?,
+            });
+        }
+        if smooth.pow(3) > *n {
+            return Some(PrimalityCertificate::BlsMinus {
+                prime: n.clone(),
+                factors: certify_factors(&factors, |q| minus_witness(n, q, false))?,
+            });
+        }
+        let plus = n + 1u32;
+        let (factors, rest) = partial_factorization(&plus, n);
+        let smooth = product(&factors);
+        if rest.is_one() || (&smooth - 1u32).pow(2) > *n {
+            let base = (3u32..)
+                .map(BigUint::from)
+                .take(WITNESS_ATTEMPTS as usize)
+                .find(|p| BigInt::from(p * p - 4u32).jacobi(BigInt::from(n.clone())) == Some(-1))?;
+            let discriminant = &base * &base - 4u32;
+            return Some(PrimalityCertificate::BlsPlus {
+                prime: n.clone(),
+                discriminant: discriminant.clone(),
+                factors: certify_factors(&factors, |q| {
+                    (0..WITNESS_ATTEMPTS)
+                        .map(|step| &base + 2 * step)
+                        .find(|p| {
+                            lucas_parameter(n, &discriminant, p).is_some_and(|lucas| {
+                                lucas_u_test(n, p, &lucas, &plus).is_zero()
+                                    && lucas_u_test(n, p, &lucas, &(&plus / q)).gcd(n).is_one()
+                            })
+                        })
+                })?,
+            });
+        }
+        Some(PrimalityCertificate::Pratt {
+            prime: n.clone(),
+            factors: certify_factors(&full_factorization(&minus), |q| minus_witness(n, q, true))?,
+        })
+    }
+    pub fn prime(&self) -> &BigUint {
+        match self {
+            PrimalityCertificate::TrialDivision { prime }
+            | PrimalityCertificate::Pratt { prime, .. }
+            | PrimalityCertificate::Pocklington { prime, .. }
+            | PrimalityCertificate::BlsMinus { prime, .. }
+            | PrimalityCertificate::BlsPlus { prime, .. } => prime,
+        }
+    }
+    fn factors(&self) -> &[CertifiedFactor] {
+        match self {
+            PrimalityCertificate::TrialDivision { .. } => &[],
+            PrimalityCertificate::Pratt { factors, .. }
+            | PrimalityCertificate::Pocklington { factors, .. }
+            | PrimalityCertificate::BlsMinus { factors, .. }
+            | PrimalityCertificate::BlsPlus { factors, .. } => factors,
+        }
+    }
+}
+fn verify_minus(n: &BigUint, factors: &[CertifiedFactor], strict: bool) -> Option<BigUint> {
+    let minus = n - 1u32;
+    let mut covered = BigUint::one();
+    for factor in factors {
+        covered *= factor.prime.pow(factor.exponent);
+        let power = factor.witness.modpow(&(&minus / &factor.prime), n);
+        let valid = if strict {
+            !power.is_one()
+        } else {
+            (power + &minus).gcd(n).is_one()
+        };
+        if !valid || !factor.witness.modpow(&minus, n).is_one() {
+            return None;
+        }
+    }
+    (&minus % &covered).is_zero().then_some(covered)
+}
+fn verify_plus(n: &BigUint, discriminant: &BigUint, factors: &[CertifiedFactor]) -> bool {
+    let plus = n + 1u32;
+    let mut covered = BigUint::one();
+    for factor in factors {
+        covered *= factor.prime.pow(factor.exponent);
+        let Some(q) = lucas_parameter(n, discriminant, &factor.witness) else {
+            return false;
+        };
+        if !lucas_u_test(n, &factor.witness, &q, &plus).is_zero()
+            || !lucas_u_test(n, &factor.witness, &q, &(&plus / &factor.prime))
+                .gcd(n)
+                .is_one()
+        {
+            return false;
+        }
+    }
+    (&plus % &covered).is_zero() && (covered - 1u32).pow(2) > *n
+}
+pub fn verify_certificate(certificate: &PrimalityCertificate) -> bool {
+    let n = certificate.prime();
+    let factors_valid = certificate.factors().iter().all(|factor| {
+        factor.exponent > 0
+            && factor.certificate.prime() == &factor.prime
+            && verify_certificate(&factor.certificate)
+    });
+    if !factors_valid {
+        return false;
+    }
+    if !matches!(certificate, PrimalityCertificate::TrialDivision { .. })
+        && (n.bits() <= 2 || n.is_even())
+    {
+        return false;
+    }
+    match certificate {
+        PrimalityCertificate::TrialDivision { prime } => {
+            prime.bits() <= TRIAL_DIVISION_BITS && trial_division(prime)
+        }
+        PrimalityCertificate::Pratt { prime, factors } => {
+            verify_minus(prime, factors, true).is_some_and(|covered| covered == prime - 1u32)
+        }
+        PrimalityCertificate::Pocklington { prime, factors } => {
+            verify_minus(prime, factors, false).is_some_and(|covered| &covered * &covered > *prime)
+        }
+        PrimalityCertificate::BlsMinus { prime, factors } => {
+            let Some(covered) = verify_minus(prime, factors, false) else {
+                return false;
+            };
+            if covered.pow(3) <= *prime {
+                return false;
+            }
+            let quotient = (prime - 1u32) / &covered;
+            let (high, low) = quotient.div_rem(&covered);
+            let determinant = BigInt::from(&low * &low) - BigInt::from(high * 4u32);
+            determinant.sign() == num_bigint::Sign::Minus || {
+                let magnitude = determinant.magnitude();
+                let root = magnitude.sqrt();
+                &root * &root != *magnitude
+            }
+        }
+        PrimalityCertificate::BlsPlus {
+            prime,
+            discriminant,
+            factors,
+        } => {
+            BigInt::from(discriminant.clone()).jacobi(BigInt::from(prime.clone())) == Some(-1)
+                && verify_plus(prime, discriminant, factors)
+        }
+    }
+}
+impl Display for PrimalityCertificate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let factors = self.factors();
+        match self {
+            PrimalityCertificate::TrialDivision { prime } => writeln!(f, "trial {prime}")?,
+            PrimalityCertificate::Pratt { prime, .. } => {
+                writeln!(f, "pratt {prime} {}", factors.len())?
+            }
+            PrimalityCertificate::Pocklington { prime, .. } => {
+                writeln!(f, "pocklington {prime} {}", factors.len())?
+            }
+            PrimalityCertificate::BlsMinus { prime, .. } => {
+                writeln!(f, "bls-minus {prime} {}", factors.len())?
+            }
+            PrimalityCertificate::BlsPlus {
+                prime,
+                discriminant,
+                ..
+            } => writeln!(f, "bls-plus {prime} {discriminant} {}", factors.len())?,
+        }
+        for factor in factors {
+            writeln!(
+                f,
+                "factor {} {} {}",
+                factor.prime, factor.exponent, factor.witness
+            )?;
+            factor.certificate.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseCertificateError {
+    line: usize,
+}
+impl Display for ParseCertificateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid primality certificate at line {}", self.line)
+    }
+}
+impl std::error::Error for ParseCertificateError {}
+fn parse_certificate(
+    lines: &mut Lines<'_>,
+    line: &mut usize,
+) -> Result<PrimalityCertificate, ParseCertificateError> {
+    let error = |line: usize| ParseCertificateError { line };
+    *line += 1;
+    let current = *line;
+    let words: Vec<&str> = lines
+        .next()
+        .ok_or(error(current))?
+        .split_whitespace()
+        .collect();
+    let number = |index: usize| -> Result<BigUint, ParseCertificateError> {
+        words
+            .get(index)
+            .and_then(|word| word.parse().ok())
+            .ok_or(error(current))
+    };
+    let count = |index: usize| -> Result<usize, ParseCertificateError> {
+        words
+            .get(index)
+            .and_then(|word| word.parse().ok())
+            .ok_or(error(current))
+    };
+    let expected = match words.first() {
+        Some(&"trial") if words.len() == 2 => 0,
+        Some(&"pratt" | &"pocklington" | &"bls-minus") if words.len() == 3 => count(2)?,
+        Some(&"bls-plus") if words.len() == 4 => count(3)?,
+        _ => return Err(error(current)),
+    };
+    let mut factors = Vec::new();
+    for _ in 0..expected {
+        *line += 1;
+        let current = *line;
+        let words: Vec<&str> = lines
+            .next()
+            .ok_or(error(current))?
+            .split_whitespace()
+            .collect();
+        if words.len() != 4 || words[0] != "factor" {
+            return Err(error(current));
+        }
+        let prime = words[1].parse().map_err(|_| error(current))?;
+        let exponent = words[2].parse().map_err(|_| error(current))?;
+        let witness = words[3].parse().map_err(|_| error(current))?;
+        let certificate = parse_certificate(lines, line)?;
+        factors.push(CertifiedFactor {
+            prime,
+            exponent,
+            witness,
+            certificate,
+        });
+    }
+    let prime = number(1)?;
+    Ok(match words[0] {
+        "trial" => PrimalityCertificate::TrialDivision { prime },
+        "pratt" => PrimalityCertificate::Pratt { prime, factors },
+        "pocklington" => PrimalityCertificate::Pocklington { prime, factors },
+        "bls-minus" => PrimalityCertificate::BlsMinus { prime, factors },
+        _ => PrimalityCertificate::BlsPlus {
+            prime,
+            discriminant: number(2)?,
+            factors,
+        },
+    })
+}
+impl FromStr for PrimalityCertificate {
+    type Err = ParseCertificateError;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut lines = text.lines();
+        let mut line = 0;
+        let certificate = parse_certificate(&mut lines, &mut line)?;
+        if lines.any(|rest| !rest.trim().is_empty()) {
+            return Err(ParseCertificateError { line: line + 1 });
+        }
+        Ok(certificate)
+    }
+}
@@ -0,0 +1,194 @@
+use super::factor::Factorize;
+use super::modular::{Modular, add_mod, power, sub_mod};
+use super::primality::PowMod;
+use milans_rust_core::random::{Rng, SplitMix64};
+use num::integer::Roots;
+use num::{FromPrimitive, Integer, ToPrimitive};
+use std::collections::HashMap;
+use std::hash::Hash;
+const BABY_STEP_LIMIT: u64 = 1 << 40;
+const RHO_ATTEMPTS: usize = 32;
+const RHO_MAX_GCD: u64 = 1 << 16;
+pub fn baby_step_giant_step<T>(base: T, target: T, modulus: T, order: T) -> Option<T>
+where
+    T: Clone + Integer + Hash + PowMod + ToPrimitive + Roots,
+{
+    let base = base.mod_floor(&modulus);
+    let target = target.mod_floor(&modulus);
+    if target == T::one().mod_floor(&modulus) {
+        return Some(T::zero());
+    }
+    let steps = order.sqrt() + T::one();
+    let mut table = HashMap::new();
+    let (mut value, mut j) = (target.clone(), T::zero());
+    while j < steps {
+        table.insert(value.clone(), j.clone());
+        value = value.mul_mod(base.clone(), modulus.clone());
+        j = j + T::one();
+    }
+    let giant = power(base.clone(), steps.clone(), &modulus);
+    let (mut value, mut offset) = (giant.clone(), T::zero());
+    while offset < order {
+        if let Some(j) = table.get(&value) {
+            let remainder = steps.clone() - j.clone();
+            if remainder <= order.clone() - offset.clone() {
+                let exponent = offset.clone() + remainder;
+                if power(base.clone(), exponent.clone(), &modulus) == target {
+                    return Some(exponent);
+                }
+            }
+        }
+        value = value.mul_mod(giant.clone(), modulus.clone());
+        offset = offset + steps.clone();
+    }
+    None
+}
+// Probabilistic: None can also mean that all RHO_ATTEMPTS walks from starts drawn from rng
+// failed, even though a logarithm exists. Calling again with the advanced rng retries.
+pub fn pollard_rho_log<T, R>(base: T, target: T, modulus: T, order: T, rng: &mut R) -> Option<T>
+where
+    T: Clone + Integer + PowMod + ToPrimitive + FromPrimitive + Modular,
+    R: Rng,
+{
+    if order.is_zero() {
+        return None;
+    }
+    let base = base.mod_floor(&modulus);
+    let target = target.mod_floor(&modulus);
+    if target == T::one().mod_floor(&modulus) {
+        return Some(T::zero());
+    }
+    let three = T::from_u8(3).unwrap();
+    let step = |(x, a, b): (T, T, T)| match x.mod_floor(&three).to_u8().unwrap() {
+        0 => (
+            x.clone().mul_mod(x, modulus.clone()),
+            add_mod(a.clone(), a, &order),
+            add_mod(b.clone(), b, &order),
+        ),
+        1 => (
+            x.mul_mod(base.clone(), modulus.clone()),
+            add_mod(a, T::one().mod_floor(&order), &order),
+            b,
+        ),
+        _ => (
+            x.mul_mod(target.clone(), modulus.clone()),
+            a,
+            add_mod(b, T::one().mod_floor(&order), &order),
+        ),
+    };
+    let bound = order.to_u64().unwrap_or(u64::MAX);
+    for _ in 0..RHO_ATTEMPTS {
+        let a = T::from_u64(rng.below(bound)).unwrap();
+        let b = T::from_u64(rng.below(bound)).unwrap();
+        let x = power(base.clone(), a.clone(), &modulus)
+            .mul_mod(power(target.clone(), b.clone(), &modulus), modulus.clone());
+        let mut tortoise = step((x, a, b));
+        let mut hare = step(tortoise.clone());
+        while tortoise.0 != hare.0 {
+            tortoise = step(tortoise);
+            hare = step(step(hare));
+        }
+        let (_, a1, b1) = tortoise;
+        let (_, a2, b2) = hare;
+        let coefficient = sub_mod(b1, b2, &order);
+        let difference = sub_mod(a2, a1, &order);
+        if coefficient.is_zero() {
+            continue;
+        }
+        let gcd = coefficient.gcd(&order);
+        if !difference.is_multiple_of(&gcd) || gcd.to_u64().is_none_or(|gcd| gcd > RHO_MAX_GCD) {
+            continue;
+        }
+        let reduced = order.clone() / gcd.clone();
+        let Some(inverse) = (coefficient / gcd.clone())
+            .mod_floor(&reduced)
+            .mod_inverse(reduced.clone())
+        else {
+            continue;
+        };
+        let mut candidate = (difference / gcd.clone()).mul_mod(inverse, reduced.clone());
+        let mut k = T::zero();
+        while k < gcd {
+            if power(base.clone(), candidate.clone(), &modulus) == target {
+                return Some(candidate);
+            }
+            candidate = candidate + reduced.clone();
+            k = k + T::one();
+        }
+    }
+    None
+}
+// A walk on a prime-order subgroup only fails with probability about 1 / prime, so the restarts
+// make a spurious None practically impossible. A target outside the subgroup, possible for a
+// composite modulus, fails every walk, which is why the restarts are bounded.
+fn prime_order_log<T, R>(base: T, target: T, modulus: T, prime: T, rng: &mut R) -> Option<T>
+where
+    T: Clone + Integer + Hash + PowMod + ToPrimitive + FromPrimitive + Roots + Modular,
+    R: Rng,
+{
+    if prime.to_u64().is_some_and(|prime| prime < BABY_STEP_LIMIT) {
+        return baby_step_giant_step(base, target, modulus, prime);
+    }
+    (0..RHO_ATTEMPTS).find_map(|_| {
+        pollard_rho_log(
+            base.clone(),
+            target.clone(),
+            modulus.clone(),
+            prime.clone(),
+            rng,
+        )
+    })
+}
+pub fn pohlig_hellman<T>(base: T, target: T, modulus: T, order: T) -> Option<T>
+where
+    T: Clone + Integer + Hash + PowMod + ToPrimitive + FromPrimitive + Roots + Modular + Factorize,
+{
+    let base = base.mod_floor(&modulus);
+    let target = target.mod_floor(&modulus);
+    let one = T::one().mod_floor(&modulus);
+    let mut rng = SplitMix64::new(order.to_u64().unwrap_or(u64::MAX));
+    let mut congruences = Vec::new();
+    for (prime, exponent) in order.clone().factorize() {
+        let prime_power = num::pow(prime.clone(), exponent as usize);
+        let cofactor = order.clone() / prime_power;
+        let generator = power(base.clone(), cofactor.clone(), &modulus);
+        let element = power(target.clone(), cofactor, &modulus);
+        let (mut exponent, mut generator_order) = (0, T::one());
+        let mut value = generator.clone();
+        while value != one {
+            value = power(value, prime.clone(), &modulus);
+            generator_order = generator_order * prime.clone();
+            exponent += 1;
+        }
+        if power(element.clone(), generator_order.clone(), &modulus) != one {
+            return None;
+        }
+        if exponent == 0 {
+            continue;
+        }
+        let step = num::pow(prime.clone(), exponent as usize - 1);
+        let gamma = power(generator.clone(), step.clone(), &modulus);
+        let (mut digits, mut place) = (T::zero(), T::one());
+        for _ in 0..exponent {
+            let inverse = power(
+                generator.clone(),
+                generator_order.clone() - digits.clone(),
+                &modulus,
+            );
+            let shifted = inverse.mul_mod(element.clone(), modulus.clone());
+            let reduced = power(shifted, step.clone() / place.clone(), &modulus);
+            let digit = prime_order_log(
+                gamma.clone(),
+                reduced,
+                modulus.clone(),
+                prime.clone(),
+                &mut rng,
+            )?;
+            digits = digits + digit * place.clone();
+            place = place * prime.clone();
+        }
+        congruences.push((digits, generator_order));
+    }
+    let (exponent, _) = T::crt(&congruences)?;
+    (power(base, exponent.clone(), &modulus) == target).then_some(exponent)
+}
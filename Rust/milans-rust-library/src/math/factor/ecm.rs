@@ -0,0 +1,223 @@
+use crate::math::sieve::Primes;
+use milans_rust_core::random::Rng;
+use num::{Integer, One, Zero};
+use num_bigint::BigUint;
+const STAGE_TWO_WIDTH: u64 = 2310;
+pub const ECM_SCHEDULE: [(u32, u64, usize); 10] = [
+    (15, 2_000, 25),
+    (20, 11_000, 90),
+    (25, 50_000, 300),
+    (30, 250_000, 700),
+    (35, 1_000_000, 1_800),
+    (40, 3_000_000, 5_100),
+    (45, 11_000_000, 10_600),
+    (50, 43_000_000, 19_300),
+    (55, 110_000_000, 49_000),
+    (60, 260_000_000, 124_000),
+];
+#[derive(Clone, Debug)]
+struct Point {
+    x: BigUint,
+    z: BigUint,
+}
+struct Curve<'a> {
+    modulus: &'a BigUint,
+    a24: BigUint,
+}
+impl Curve<'_> {
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let sum = a + b;
+        if sum >= *self.modulus {
+            sum - self.modulus
+        } else {
+            sum
+        }
+    }
+    fn sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        if a >= b { a - b } else { a + self.modulus - b }
+    }
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b % self.modulus
+    }
+    fn double(&self, point: &Point) -> Point {
+        let sum = self.add(&point.x, &point.z);
+        let sum = self.mul(&sum, &sum);
+        let difference = self.sub(&point.x, &point.z);
+        let difference = self.mul(&difference, &difference);
+        let cross = self.sub(&sum, &difference);
+        Point {
+            x: self.mul(&sum, &difference),
+            z: self.mul(&cross, &self.add(&difference, &self.mul(&self.a24, &cross))),
+        }
+    }
+    fn differential_add(&self, a: &Point, b: &Point, difference: &Point) -> Point {
+        let u = self.mul(&self.sub(&a.x, &a.z), &self.add(&b.x, &b.z));
+        let v = self.mul(&self.add(&a.x, &a.z), &self.sub(&b.x, &b.z));
+        let sum = self.add(&u, &v);
+        let cross = self.sub(&u, &v);
+        Point {
+            x: self.mul(&difference.z, &self.mul(&sum, &sum)),
+            z: self.mul(&difference.x, &self.mul(&cross, &cross)),
+        }
+    }
+    fn multiply(&self, point: &Point, scalar: u64) -> Point {
+        if scalar == 1 {
+            return point.clone();
+        }
+        let mut low = point.clone();
+        let mut high = self.double(point);
+        for bit in (0..63 - scalar.leading_zeros()).rev() {
+            if (scalar >> bit) & 1 == 1 {
+                low = self.differential_add(&low, &high, point);
+                high = self.double(&high);
+            } else {
+                high = self.differential_add(&low, &high, point);
+                low = self.double(&low);
+            }
+        }
+        low
+    }
+}
+fn nontrivial_factor(value: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let divisor = value.gcd(modulus);
+    if divisor.is_one() || divisor == *modulus {
+        None
+    } else {
+        Some(divisor)
+    }
+}
+fn random_below<R>(bound: &BigUint, rng: &mut R) -> BigUint
+where
+    R: Rng,
+{
+    let mut value = BigUint::zero();
+    for _ in 0..bound.bits().div_ceil(64) + 1 {
+        value = (value << 64u32) + rng.next_u64();
+    }
+    value % bound
+}
+fn stage_two(curve: &Curve, point: &Point, b1: u64, b2: u64) -> Option<BigUint> {
+    let double = curve.double(point);
+    let mut baby_steps = vec![point.clone(), curve.differential_add(&double, point, point)];
+    while baby_steps.len() < STAGE_TWO_WIDTH as usize / 4 + 1 {
+        let length = baby_steps.len();
+        let next =
+            curve.differential_add(&baby_steps[length - 1], &double, &baby_steps[length - 2]);
+        baby_steps.push(next);
+    }
+    let infinity = Point {
+        x: BigUint::one(),
+        z: BigUint::zero(),
+    };
+    let giant = curve.multiply(point, STAGE_TWO_WIDTH);
+    let start = b1.max(11) + 1;
+    let mut m = (start + STAGE_TWO_WIDTH / 2) / STAGE_TWO_WIDTH;
+    let mut current = if m == 0 {
+        infinity.clone()
+    } else {
+        curve.multiply(point, m * STAGE_TWO_WIDTH)
+    };
+    let mut previous = if m <= 1 {
+        infinity
+    } else {
+        curve.multiply(point, (m - 1) * STAGE_TWO_WIDTH)
+    };
+    let mut accumulator = BigUint::one();
+    for prime in Primes::starting_at(start).take_while(|prime| *prime <= b2) {
+        while m < (prime + STAGE_TWO_WIDTH / 2) / STAGE_TWO_WIDTH {
+            let next = match m {
+                0 => giant.clone(),
+                1 => curve.double(&current),
+                _ => curve.differential_add(&current, &giant, &previous),
+            };
+            previous = std::mem::replace(&mut current, next);
+            m += 1;
+        }
+        let baby = &baby_steps[(prime.abs_diff(m * STAGE_TWO_WIDTH) / 2) as usize];
+        let term = curve.sub(
+            &curve.mul(&current.x, &baby.z),
+            &curve.mul(&baby.x, &current.z),
+        );
+        accumulator = curve.mul(&accumulator, &term);
+    }
+    nontrivial_factor(&accumulator, curve.modulus)
+}
+pub fn ecm_find_factor<R>(
+    n: &BigUint,
+    b1: u64,
+    b2: u64,
+    curves: usize,
+    rng: &mut R,
+) -> Option<BigUint>
+where
+    R: Rng,
+{
+    if n.is_even() {
+        return (*n > BigUint::from(2u32)).then(|| BigUint::from(2u32));
+    }
+    if *n < BigUint::from(7u32) {
+        return None;
+    }
+    let prime_powers: Vec<u64> = Primes::new()
+        .take_while(|prime| *prime <= b1)
+        .map(|prime| {
+            let mut power = prime;
+            while power <= b1 / prime {
+                power *= prime;
+            }
+            power
+        })
+        .collect();
+    for _ in 0..curves {
+        let sigma = random_below(&(n - 6u32), rng) + 6u32;
+        let u = (&sigma * &sigma + n - 5u32) % n;
+        let v = (&sigma << 2u32) % n;
+        let u_cubed = u.modpow(&BigUint::from(3u32), n);
+        let v_minus_u = (&v + n - &u) % n;
+        let numerator = v_minus_u.modpow(&BigUint::from(3u32), n) * ((&u * 3u32 + &v) % n) % n;
+        let denominator = (&u_cubed * &v * 16u32) % n;
+        let Some(inverse) = denominator.modinv(n) else {
+            if let Some(factor) = nontrivial_factor(&denominator, n) {
+                return Some(factor);
+            }
+            continue;
+        };
+        let curve = Curve {
+            modulus: n,
+            a24: numerator * inverse % n,
+        };
+        let mut point = Point {
+            x: u_cubed,
+            z: v.modpow(&BigUint::from(3u32), n),
+        };
+        for power in &prime_powers {
+            point = curve.multiply(&point, *power);
+        }
+        match point.z.gcd(n) {
+            divisor if divisor.is_one() => {}
+            divisor if divisor == *n => continue,
+            divisor => return Some(divisor),
+        }
+        if b2 > b1
+            && let Some(factor) = stage_two(&curve, &point, b1, b2)
+        {
+            return Some(factor);
+        }
+    }
+    None
+}
+pub fn ecm_find_factor_default<R>(n: &BigUint, rng: &mut R) -> Option<BigUint>
+where
+    R: Rng,
+{
+    let digits = n.to_string().len() as u32;
+    for (factor_digits, b1, curves) in ECM_SCHEDULE {
+        if let Some(factor) = ecm_find_factor(n, b1, 100 * b1, curves, rng) {
+            return Some(factor);
+        }
+        if factor_digits >= digits.div_ceil(2) {
+            break;
+        }
+    }
+    None
+}
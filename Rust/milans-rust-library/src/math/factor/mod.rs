@@ -0,0 +1,423 @@
+pub mod ecm;
+pub mod qs;
+use super::modular::Modular;
+use super::montgomery::{Montgomery, MontgomeryWord};
+use super::primality::MillerRabinTest;
+use super::sieve::Primes;
+use milans_rust_core::random::{Rng, SplitMix64};
+use num_bigint::BigUint;
+use std::collections::BTreeMap;
+const TRIAL_DIVISION_LIMIT: u64 = 1 << 10;
+const RHO_ATTEMPTS: u64 = 8;
+const QUADRATIC_SIEVE_DIGITS: usize = 100;
+const RHO_ITERATIONS: usize = 1 << 20;
+const SQUFOF_MULTIPLIERS: [u64; 16] = [
+    1, 3, 5, 7, 11, 15, 21, 33, 35, 55, 77, 105, 165, 231, 385, 1155,
+];
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Factorization<T> {
+    sign: i8,
+    factors: BTreeMap<T, u32>,
+}
+impl<T> Factorization<T>
+where
+    T: Ord,
+{
+    pub fn new() -> Self {
+        Factorization {
+            sign: 1,
+            factors: BTreeMap::new(),
+        }
+    }
+    pub fn zero() -> Self {
+        Factorization {
+            sign: 0,
+            factors: BTreeMap::new(),
+        }
+    }
+    pub fn sign(&self) -> i8 {
+        self.sign
+    }
+    pub fn is_zero(&self) -> bool {
+        self.sign == 0
+    }
+    pub fn negate(&mut self) {
+        self.sign = -self.sign;
+    }
+    pub fn insert(&mut self, prime: T, exponent: u32) {
+        if exponent > 0 {
+            *self.factors.entry(prime).or_insert(0) += exponent;
+        }
+    }
+    pub fn exponent(&self, prime: &T) -> u32 {
+        self.factors.get(prime).copied().unwrap_or(0)
+    }
+    pub fn primes(&self) -> impl Iterator<Item = &T> {
+        self.factors.keys()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&T, &u32)> {
+        self.factors.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.factors.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+    pub fn as_map(&self) -> &BTreeMap<T, u32> {
+        &self.factors
+    }
+    pub fn into_map(self) -> BTreeMap<T, u32> {
+        self.factors
+    }
+}
+impl<T> Default for Factorization<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Factorization::new()
+    }
+}
+impl<T> IntoIterator for Factorization<T> {
+    type Item = (T, u32);
+    type IntoIter = std::collections::btree_map::IntoIter<T, u32>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.factors.into_iter()
+    }
+}
+fn trial_division<T>(n: T, factors: &mut Factorization<T>) -> T
+where
+    T: MontgomeryWord,
+{
+    let mut n = n;
+    let twos = n.trailing_zeros();
+    if twos > 0 {
+        factors.insert(T::from(2).unwrap(), twos);
+        n = n >> twos as usize;
+    }
+    let mut divisor = 3u64;
+    while divisor < TRIAL_DIVISION_LIMIT {
+        let prime = T::from(divisor).unwrap();
+        if prime * prime > n {
+            break;
+        }
+        let mut exponent = 0;
+        while n % prime == T::zero() {
+            n = n / prime;
+            exponent += 1;
+        }
+        factors.insert(prime, exponent);
+        divisor += 2;
+    }
+    n
+}
+fn pollard_brent<T>(n: T, increment: T, iterations: usize) -> Option<T>
+where
+    T: MontgomeryWord + Modular,
+{
+    const BATCH: usize = 128;
+    let montgomery = Montgomery::new(n)?;
+    let increment = montgomery.to_montgomery(increment);
+    let step = |value: T| montgomery.add(montgomery.square(value), increment);
+    let mut y = montgomery.to_montgomery(T::from(2).unwrap());
+    let mut x = y;
+    let mut saved = y;
+    let mut product = montgomery.one();
+    let mut divisor = T::one();
+    let mut length = 1;
+    let mut total = 0;
+    while divisor == T::one() {
+        x = y;
+        for _ in 0..length {
+            y = step(y);
+        }
+        let mut done = 0;
+        while done < length && divisor == T::one() {
+            saved = y;
+            for _ in 0..BATCH.min(length - done) {
+                y = step(y);
+                product = montgomery.mul(product, montgomery.sub(x, y));
+            }
+            divisor = product.binary_gcd(n);
+            done += BATCH;
+        }
+        total += length;
+        length *= 2;
+        if divisor == T::one() && total > iterations {
+            return None;
+        }
+    }
+    if divisor == n {
+        loop {
+            saved = step(saved);
+            divisor = montgomery.sub(x, saved).binary_gcd(n);
+            if divisor != T::one() {
+                break;
+            }
+        }
+    }
+    if divisor == n { None } else { Some(divisor) }
+}
+fn squfof(n: u64) -> Option<u64> {
+    let root = n.isqrt();
+    if root * root == n {
+        return Some(root);
+    }
+    for multiplier in SQUFOF_MULTIPLIERS {
+        let Some(scaled) = n.checked_mul(multiplier) else {
+            break;
+        };
+        let p0 = scaled.isqrt();
+        let (mut p, mut p_previous) = (p0, p0);
+        let mut q_previous = 1u64;
+        let mut q = scaled - p0 * p0;
+        if q == 0 {
+            continue;
+        }
+        let bound = 6 * (2 * root).isqrt();
+        let mut r = 0;
+        let mut found = false;
+        for i in 2..bound {
+            let b = (p0 + p) / q;
+            p = b * q - p;
+            let q_old = q;
+            q = q_previous.wrapping_add(b.wrapping_mul(p_previous.wrapping_sub(p)));
+            r = q.isqrt();
+            if i & 1 == 0 && r * r == q {
+                found = true;
+                break;
+            }
+            q_previous = q_old;
+            p_previous = p;
+        }
+        if !found || r == 0 {
+            continue;
+        }
+        let b = (p0 - p) / r;
+        p += b * r;
+        p_previous = p;
+        q_previous = r;
+        q = (scaled - p_previous * p_previous) / q_previous;
+        if q == 0 {
+            continue;
+        }
+        for _ in 0..bound {
+            let b = (p0 + p) / q;
+            p_previous = p;
+            p = b * q - p;
+            let q_old = q;
+            q = q_previous.wrapping_add(b.wrapping_mul(p_previous.wrapping_sub(p)));
+            q_previous = q_old;
+            if p == p_previous {
+                break;
+            }
+        }
+        let divisor = n.binary_gcd(q_previous);
+        if divisor != 1 && divisor != n {
+            return Some(divisor);
+        }
+    }
+    None
+}
+fn find_divisor<T>(n: T) -> T
+where
+    T: MontgomeryWord + Modular,
+{
+    for increment in 1..=RHO_ATTEMPTS {
+        if let Some(divisor) = pollard_brent(n, T::from(increment).unwrap(), RHO_ITERATIONS) {
+            return divisor;
+        }
+    }
+    if let Some(divisor) = n.to_u64().and_then(squfof) {
+        return T::from(divisor).unwrap();
+    }
+    let mut increment = RHO_ATTEMPTS + 1;
+    loop {
+        if let Some(divisor) = pollard_brent(n, T::from(increment).unwrap(), usize::MAX) {
+            return divisor;
+        }
+        increment += 1;
+    }
+}
+fn split<T>(n: T, exponent: u32, factors: &mut Factorization<T>, is_prime: fn(T) -> bool)
+where
+    T: MontgomeryWord + Modular,
+{
+    if n == T::one() {
+        return;
+    }
+    if is_prime(n) {
+        factors.insert(n, exponent);
+        return;
+    }
+    let divisor = find_divisor(n);
+    let mut rest = n / divisor;
+    let mut multiplicity = 1;
+    while rest % divisor == T::zero() {
+        rest = rest / divisor;
+        multiplicity += 1;
+    }
+    split(divisor, exponent * multiplicity, factors, is_prime);
+    split(rest, exponent, factors, is_prime);
+}
+fn factorize_word<T>(n: T, is_prime: fn(T) -> bool) -> Factorization<T>
+where
+    T: MontgomeryWord + Modular,
+{
+    if n == T::zero() {
+        return Factorization::zero();
+    }
+    let mut factors = Factorization::new();
+    let rest = trial_division(n, &mut factors);
+    split(rest, 1, &mut factors, is_prime);
+    factors
+}
+pub trait Factorize: Sized {
+    fn factorize(self) -> Factorization<Self>;
+}
+macro_rules! factorize {
+    ($type:ty,$word:ty,$magnitude:expr,$negative:expr) => {
+        impl Factorize for $type {
+            fn factorize(self) -> Factorization<Self> {
+                if self == 0 {
+                    return Factorization::zero();
+                }
+                let mut factors = Factorization::new();
+                let magnitude: fn($type) -> $word = $magnitude;
+                let negative: fn($type) -> bool = $negative;
+                for (prime, exponent) in
+                    factorize_word(magnitude(self), MillerRabinTest::miller_rabin_test)
+                {
+                    factors.insert(prime as $type, exponent);
+                }
+                if negative(self) {
+                    factors.negate();
+                }
+                factors
+            }
+        }
+    };
+}
+factorize!(u8, u64, |n| n as u64, |_| false);
+factorize!(i8, u64, |n| n.unsigned_abs() as u64, |n| n < 0);
+factorize!(u16, u64, |n| n as u64, |_| false);
+factorize!(i16, u64, |n| n.unsigned_abs() as u64, |n| n < 0);
+factorize!(u32, u64, |n| n as u64, |_| false);
+factorize!(i32, u64, |n| n.unsigned_abs() as u64, |n| n < 0);
+factorize!(u64, u64, |n| n, |_| false);
+factorize!(i64, u64, |n| n.unsigned_abs(), |n| n < 0);
+factorize!(usize, u64, |n| n as u64, |_| false);
+factorize!(isize, u64, |n| n.unsigned_abs() as u64, |n| n < 0);
+impl Factorize for u128 {
+    fn factorize(self) -> Factorization<Self> {
+        if self <= u64::MAX as u128 {
+            let small = (self as u64).factorize();
+            let mut factors = if small.is_zero() {
+                Factorization::zero()
+            } else {
+                Factorization::new()
+            };
+            for (prime, exponent) in small {
+                factors.insert(prime as u128, exponent);
+            }
+            return factors;
+        }
+        factorize_word(self, |n| {
+            if n <= u64::MAX as u128 {
+                (n as u64).miller_rabin_test()
+            } else {
+                n.miller_rabin_test()
+            }
+        })
+    }
+}
+impl Factorize for i128 {
+    fn factorize(self) -> Factorization<Self> {
+        if self == 0 {
+            return Factorization::zero();
+        }
+        let mut factors = Factorization::new();
+        for (prime, exponent) in self.unsigned_abs().factorize() {
+            factors.insert(prime as i128, exponent);
+        }
+        if self < 0 {
+            factors.negate();
+        }
+        factors
+    }
+}
+fn split_big<R>(n: BigUint, exponent: u32, factors: &mut Factorization<BigUint>, rng: &mut R)
+where
+    R: Rng,
+{
+    use num::{One, ToPrimitive, Zero};
+    if n.is_one() {
+        return;
+    }
+    if let Some(small) = n.to_u128() {
+        for (prime, multiplicity) in small.factorize() {
+            factors.insert(BigUint::from(prime), exponent * multiplicity);
+        }
+        return;
+    }
+    if (&n).miller_rabin_test() {
+        factors.insert(n, exponent);
+        return;
+    }
+    // Primes below TRIAL_DIVISION_LIMIT = 2^10 are divided out before split_big runs, so
+    // n = r^k forces r >= 2^10 and k <= log2(n) / 10.
+    let max_power = n.bits() / TRIAL_DIVISION_LIMIT.ilog2() as u64;
+    for power in Primes::new().take_while(|&power| power <= max_power) {
+        let root = n.nth_root(power as u32);
+        if root.pow(power as u32) == n {
+            split_big(root, exponent * power as u32, factors, rng);
+            return;
+        }
+    }
+    let (_, b1, curves) = ecm::ECM_SCHEDULE[0];
+    let divisor = ecm::ecm_find_factor(&n, b1, 100 * b1, curves, rng)
+        .or_else(|| {
+            (n.to_string().len() <= QUADRATIC_SIEVE_DIGITS)
+                .then(|| qs::siqs_find_factor(&n))
+                .flatten()
+        })
+        .unwrap_or_else(|| {
+            loop {
+                if let Some(divisor) = ecm::ecm_find_factor_default(&n, rng) {
+                    break divisor;
+                }
+            }
+        });
+    let mut rest = &n / &divisor;
+    let mut multiplicity = 1;
+    while (&rest % &divisor).is_zero() {
+        rest /= &divisor;
+        multiplicity += 1;
+    }
+    split_big(divisor, exponent * multiplicity, factors, rng);
+    split_big(rest, exponent, factors, rng);
+}
+impl Factorize for BigUint {
+    fn factorize(self) -> Factorization<Self> {
+        use num::{ToPrimitive, Zero};
+        if self.is_zero() {
+            return Factorization::zero();
+        }
+        let mut factors = Factorization::new();
+        let mut rest = self;
+        let mut divisor = 2u64;
+        while divisor < TRIAL_DIVISION_LIMIT && rest.to_u128().is_none() {
+            let mut exponent = 0;
+            while (&rest % divisor).is_zero() {
+                rest /= divisor;
+                exponent += 1;
+            }
+            factors.insert(BigUint::from(divisor), exponent);
+            divisor += 1 + (divisor > 2) as u64;
+        }
+        let seed = (&rest % u64::MAX).to_u64().unwrap();
+        split_big(rest, 1, &mut factors, &mut SplitMix64::new(seed));
+        factors
+    }
+}
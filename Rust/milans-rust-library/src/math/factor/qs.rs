@@ -0,0 +1,552 @@
+use crate::math::modsqrt::SqrtMod;
+use crate::math::primality::{MillerRabinTest, PowMod};
+use crate::math::sieve::Primes;
+use milans_rust_core::random::{Rng, SplitMix64};
+use num::{Integer, One, Signed, ToPrimitive, Zero};
+use num_bigint::{BigInt, BigUint};
+use std::collections::{HashMap, HashSet};
+const BLOCK_SIZE: usize = 1 << 15;
+const SMALL_PRIME_LIMIT: u32 = 128;
+const SMALL_PRIME_CORRECTION: f64 = 10.0;
+const EXTRA_RELATIONS: usize = 64;
+const A_CANDIDATES: usize = 60;
+const A_ATTEMPTS: usize = 1_000;
+const MULTIPLIERS: [u64; 20] = [
+    1, 3, 5, 7, 11, 13, 15, 17, 19, 21, 23, 29, 31, 33, 35, 37, 39, 41, 43, 47,
+];
+const PARAMETERS: [(usize, usize, usize, u64); 9] = [
+    (20, 150, 1, 30),
+    (30, 300, 1, 50),
+    (40, 700, 1, 100),
+    (50, 1_500, 2, 200),
+    (60, 4_000, 2, 300),
+    (70, 10_000, 3, 300),
+    (80, 20_000, 4, 400),
+    (90, 28_000, 6, 500),
+    (100, 36_000, 8, 600),
+];
+struct FactorBasePrime {
+    prime: u32,
+    sqrt: u32,
+    log: u8,
+}
+struct Relation {
+    value: BigUint,
+    factors: Vec<usize>,
+    large_prime: u64,
+}
+struct Polynomial {
+    a: BigInt,
+    b: BigInt,
+    c: BigInt,
+    factors: Vec<usize>,
+}
+struct QuadraticSieve {
+    n: BigUint,
+    kn: BigUint,
+    factor_base: Vec<FactorBasePrime>,
+    sieve_start: usize,
+    half_width: usize,
+    large_prime_bound: u64,
+    threshold: u8,
+    rng: SplitMix64,
+    used: HashSet<Vec<usize>>,
+    relations: Vec<Relation>,
+    partials: HashMap<u64, Relation>,
+}
+fn choose_multiplier(n: &BigUint) -> u64 {
+    let mut best = (f64::MIN, 1);
+    for k in MULTIPLIERS {
+        let kn = n * k;
+        let mut score = -0.5 * (k as f64).ln();
+        score += match (&kn % 8u32).to_u32().unwrap() {
+            1 => 2.0,
+            5 => 1.0,
+            _ => 0.5,
+        } * 2f64.ln();
+        for p in Primes::new().skip(1).take_while(|p| *p < 1000) {
+            let residue = (&kn % p).to_u64().unwrap();
+            if residue == 0 {
+                score += (p as f64).ln() / p as f64;
+            } else if residue.pow_mod((p as u128 - 1) / 2, p) == 1 {
+                score += 2.0 * (p as f64).ln() / (p - 1) as f64;
+            }
+        }
+        if score > best.0 {
+            best = (score, k);
+        }
+    }
+    best.1
+}
+impl QuadraticSieve {
+    fn new(n: &BigUint) -> Result<Self, BigUint> {
+        let digits = n.to_string().len();
+        let (_, size, blocks, multiplier) = PARAMETERS
+            .iter()
+            .copied()
+            .find(|parameters| digits <= parameters.0)
+            .unwrap_or(PARAMETERS[PARAMETERS.len() - 1]);
+        let k = choose_multiplier(n);
+        let kn = n * k;
+        let mut factor_base = vec![FactorBasePrime {
+            prime: 2,
+            sqrt: 1,
+            log: 1,
+        }];
+        for p in Primes::new().skip(1) {
+            if factor_base.len() >= size {
+                break;
+            }
+            if (n % p).is_zero() {
+                return Err(BigUint::from(p));
+            }
+            let residue = (&kn % p).to_u64().unwrap();
+            if residue == 0 || residue.pow_mod((p as u128 - 1) / 2, p) == 1 {
+                factor_base.push(FactorBasePrime {
+                    prime: p as u32,
+                    sqrt: residue.tonelli_shanks(p).unwrap() as u32,
+                    log: (p as f64).log2().round() as u8,
+                });
+            }
+        }
+        let sieve_start = factor_base
+            .iter()
+            .position(|entry| entry.prime >= SMALL_PRIME_LIMIT)
+            .unwrap_or(factor_base.len());
+        let largest = factor_base[factor_base.len() - 1].prime as u64;
+        let half_width = blocks * BLOCK_SIZE;
+        let large_prime_bound = largest * multiplier;
+        let maximum = (half_width as f64).log2() + kn.bits() as f64 / 2.0 - 0.5;
+        let threshold = maximum - (large_prime_bound as f64).log2() - SMALL_PRIME_CORRECTION;
+        Ok(QuadraticSieve {
+            n: n.clone(),
+            kn,
+            factor_base,
+            sieve_start,
+            half_width,
+            large_prime_bound,
+            threshold: threshold.max(1.0) as u8,
+            rng: SplitMix64::new((n % u64::MAX).to_u64().unwrap()),
+            used: HashSet::new(),
+            relations: Vec::new(),
+            partials: HashMap::new(),
+        })
+    }
+    fn choose_a(&mut self) -> Option<(BigUint, Vec<usize>)> {
+        let target = (&self.kn * 2u32).sqrt() / self.half_width;
+        let target_log = target.to_f64().unwrap().log2();
+        let eligible: Vec<usize> = (self.sieve_start..self.factor_base.len())
+            .filter(|i| self.factor_base[*i].sqrt != 0)
+            .collect();
+        let largest_log = (self.factor_base[self.factor_base.len() - 1].prime as f64).log2();
+        let mut count = (target_log / 11.0).round().max(2.0) as usize;
+        while target_log / (count as f64) > largest_log - 1.0 {
+            count += 1;
+        }
+        if eligible.len() < count {
+            return None;
+        }
+        let ideal = target_log / count as f64;
+        let distance = |i: &usize| ((self.factor_base[*i].prime as f64).log2() - ideal).abs();
+        let mut candidates = eligible.clone();
+        candidates.sort_by(|x, y| distance(x).total_cmp(&distance(y)));
+        candidates.truncate(A_CANDIDATES.max(3 * count));
+        // Once the candidates near the ideal size are used up, every draw repeats an old a.
+        for _ in 0..A_ATTEMPTS {
+            let mut chosen: Vec<usize> = Vec::with_capacity(count);
+            while chosen.len() < count - 1 {
+                let index = candidates[self.rng.below(candidates.len() as u64) as usize];
+                if !chosen.contains(&index) {
+                    chosen.push(index);
+                }
+            }
+            let product_log: f64 = chosen
+                .iter()
+                .map(|i| (self.factor_base[*i].prime as f64).log2())
+                .sum();
+            let remaining = target_log - product_log;
+            let mut last: Vec<usize> = eligible
+                .iter()
+                .copied()
+                .filter(|i| !chosen.contains(i))
+                .collect();
+            last.sort_by(|x, y| {
+                let x = ((self.factor_base[*x].prime as f64).log2() - remaining).abs();
+                let y = ((self.factor_base[*y].prime as f64).log2() - remaining).abs();
+                x.total_cmp(&y)
+            });
+            chosen.push(last[self.rng.below(last.len().min(4) as u64) as usize]);
+            chosen.sort_unstable();
+            if self.used.insert(chosen.clone()) {
+                let a = chosen
+                    .iter()
+                    .fold(BigUint::one(), |a, i| a * self.factor_base[*i].prime);
+                return Some((a, chosen));
+            }
+        }
+        None
+    }
+    fn sieve_batch(&mut self, sieve: &mut [u8]) -> Option<()> {
+        let (a, indices) = self.choose_a()?;
+        let size = self.factor_base.len();
+        let width = self.half_width as u64;
+        let mut terms = Vec::with_capacity(indices.len());
+        for i in &indices {
+            let q = self.factor_base[*i].prime as u64;
+            let cofactor = &a / q;
+            let inverse = (&cofactor % q).to_u64().unwrap().pow_mod(q as u128 - 2, q);
+            let mut gamma = (self.factor_base[*i].sqrt as u64).mul_mod(inverse, q);
+            if gamma > q / 2 {
+                gamma = q - gamma;
+            }
+            terms.push(cofactor * gamma);
+        }
+        let mut b: BigInt = terms
+            .iter()
+            .fold(BigUint::zero(), |b, term| b + term)
+            .into();
+        let mut active = vec![false; size];
+        let mut roots = vec![(0u32, 0u32); size];
+        let mut deltas = vec![vec![0u32; size]; terms.len()];
+        for i in 1..size {
+            let FactorBasePrime { prime, sqrt, .. } = self.factor_base[i];
+            let p = prime as u64;
+            if sqrt == 0 || indices.contains(&i) {
+                continue;
+            }
+            active[i] = true;
+            let inverse = (&a % p).to_u64().unwrap().pow_mod(p as u128 - 2, p);
+            let b_mod = (b.magnitude() % p).to_u64().unwrap();
+            let first = inverse.mul_mod(sqrt as u64 + p - b_mod, p);
+            let second = inverse.mul_mod(2 * p - sqrt as u64 - b_mod, p);
+            roots[i] = (((first + width) % p) as u32, ((second + width) % p) as u32);
+            for (l, term) in terms.iter().enumerate() {
+                deltas[l][i] = (2 * (term % p).to_u64().unwrap()).mul_mod(inverse, p) as u32;
+            }
+        }
+        let a: BigInt = a.into();
+        let mut polynomial = Polynomial {
+            c: (&b * &b - BigInt::from(self.kn.clone())) / &a,
+            a,
+            b: b.clone(),
+            factors: indices,
+        };
+        let mask = u64::from_ne_bytes([!((1u8 << self.threshold.ilog2()) - 1); 8]);
+        for index in 0..1u64 << (terms.len() - 1) {
+            if index > 0 {
+                let v = index.trailing_zeros() as usize;
+                let subtract = ((index ^ (index >> 1)) >> v) & 1 == 1;
+                let step: BigInt = (&terms[v] << 1u32).into();
+                for i in 1..size {
+                    if !active[i] {
+                        continue;
+                    }
+                    let p = self.factor_base[i].prime;
+                    let delta = if subtract {
+                        deltas[v][i]
+                    } else {
+                        p - deltas[v][i]
+                    };
+                    let (first, second) = roots[i];
+                    roots[i] = ((first + delta) % p, (second + delta) % p);
+                }
+                if subtract {
+                    b -= step;
+                } else {
+                    b += step;
+                }
+                polynomial.c = (&b * &b - BigInt::from(self.kn.clone())) / &polynomial.a;
+                polynomial.b = b.clone();
+            }
+            sieve.fill(0);
+            for i in self.sieve_start..size {
+                if !active[i] {
+                    continue;
+                }
+                let FactorBasePrime { prime, log, .. } = self.factor_base[i];
+                let (first, second) = roots[i];
+                for root in [first, second] {
+                    let mut j = root as usize;
+                    while j < sieve.len() {
+                        sieve[j] = sieve[j].wrapping_add(log);
+                        j += prime as usize;
+                    }
+                }
+            }
+            for (chunk, bytes) in sieve.chunks_exact(8).enumerate() {
+                if u64::from_ne_bytes(bytes.try_into().unwrap()) & mask == 0 {
+                    continue;
+                }
+                for (offset, byte) in bytes.iter().enumerate() {
+                    if *byte >= self.threshold {
+                        self.check_candidate(8 * chunk + offset, &polynomial, &active, &roots);
+                    }
+                }
+            }
+        }
+        Some(())
+    }
+    fn check_candidate(
+        &mut self,
+        j: usize,
+        polynomial: &Polynomial,
+        active: &[bool],
+        roots: &[(u32, u32)],
+    ) {
+        let x = BigInt::from(j as i64 - self.half_width as i64);
+        let value = (&polynomial.a * &x + (&polynomial.b << 1u32)) * &x + &polynomial.c;
+        if value.is_zero() {
+            return;
+        }
+        let mut factors: Vec<usize> = polynomial.factors.iter().map(|i| i + 1).collect();
+        if value.is_negative() {
+            factors.push(0);
+        }
+        let mut rest = value.magnitude().clone();
+        let twos = rest.trailing_zeros().unwrap();
+        rest >>= twos;
+        factors.extend(std::iter::repeat_n(1, twos as usize));
+        for i in 1..self.factor_base.len() {
+            let p = self.factor_base[i].prime;
+            let divides = if active[i] {
+                let position = (j % p as usize) as u32;
+                position == roots[i].0 || position == roots[i].1
+            } else {
+                (&rest % p).is_zero()
+            };
+            if divides {
+                while (&rest % p).is_zero() {
+                    rest /= p;
+                    factors.push(i + 1);
+                }
+            }
+        }
+        let large_prime = match rest.to_u64() {
+            Some(1) => 1,
+            Some(large_prime) if large_prime < self.large_prime_bound => large_prime,
+            _ => return,
+        };
+        let value = (&polynomial.a * &x + &polynomial.b)
+            .mod_floor(&BigInt::from(self.n.clone()))
+            .magnitude()
+            .clone();
+        let relation = Relation {
+            value,
+            factors,
+            large_prime: 1,
+        };
+        if large_prime == 1 {
+            self.relations.push(relation);
+        } else if let Some(partner) = self.partials.get(&large_prime) {
+            let mut factors = relation.factors;
+            factors.extend_from_slice(&partner.factors);
+            self.relations.push(Relation {
+                value: relation.value * &partner.value % &self.n,
+                factors,
+                large_prime,
+            });
+        } else {
+            self.partials.insert(large_prime, relation);
+        }
+    }
+    fn square_root(&self, dependency: &[usize]) -> Option<BigUint> {
+        let mut x = BigUint::one();
+        let mut y = BigUint::one();
+        let mut counts = vec![0u32; self.factor_base.len() + 1];
+        for relation in dependency.iter().map(|r| &self.relations[*r]) {
+            x = x * &relation.value % &self.n;
+            y = y * relation.large_prime % &self.n;
+            for column in &relation.factors {
+                counts[*column] += 1;
+            }
+        }
+        for (column, count) in counts.iter().enumerate().skip(1) {
+            let prime = BigUint::from(self.factor_base[column - 1].prime);
+            y = y * prime.modpow(&BigUint::from(count / 2), &self.n) % &self.n;
+        }
+        let divisor = ((x + &self.n - y) % &self.n).gcd(&self.n);
+        (!divisor.is_one() && divisor != self.n).then_some(divisor)
+    }
+    fn run(&mut self) -> Option<BigUint> {
+        let columns = self.factor_base.len() + 1;
+        let mut target = columns + EXTRA_RELATIONS;
+        let mut sieve = vec![0u8; 2 * self.half_width];
+        loop {
+            while self.relations.len() < target {
+                self.sieve_batch(&mut sieve)?;
+            }
+            for dependency in find_dependencies(&self.relations, columns) {
+                if let Some(divisor) = self.square_root(&dependency) {
+                    return Some(divisor);
+                }
+            }
+            target = self.relations.len() + EXTRA_RELATIONS;
+        }
+    }
+}
+fn symmetric_difference(x: &[usize], y: &[usize]) -> Vec<usize> {
+    let mut result = Vec::with_capacity(x.len() + y.len());
+    let (mut i, mut j) = (0, 0);
+    while i < x.len() && j < y.len() {
+        match x[i].cmp(&y[j]) {
+            std::cmp::Ordering::Less => {
+                result.push(x[i]);
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                result.push(y[j]);
+                j += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    result.extend_from_slice(&x[i..]);
+    result.extend_from_slice(&y[j..]);
+    result
+}
+// Structured Gaussian elimination: rows are relations, kept as sorted lists of odd
+// columns together with the set of relations they combine. Singleton columns drop
+// their row, columns of weight two merge their rows, and surplus heavy rows go,
+// which leaves a much smaller matrix for the dense elimination.
+fn reduce_matrix(
+    parities: &mut [Vec<usize>],
+    combined: &mut [Vec<usize>],
+    columns: usize,
+) -> Vec<usize> {
+    let mut active = vec![true; parities.len()];
+    let mut holders = vec![Vec::new(); columns];
+    loop {
+        for holder in holders.iter_mut() {
+            holder.clear();
+        }
+        for (r, parity) in parities.iter().enumerate() {
+            if active[r] {
+                for column in parity {
+                    holders[*column].push(r);
+                }
+            }
+        }
+        let mut changed = false;
+        let mut touched = vec![false; parities.len()];
+        for holder in &holders {
+            match holder[..] {
+                [r] if active[r] && !touched[r] => {
+                    active[r] = false;
+                    changed = true;
+                }
+                [r, s] if active[r] && active[s] && !touched[r] && !touched[s] => {
+                    parities[s] = symmetric_difference(&parities[r], &parities[s]);
+                    combined[s] = symmetric_difference(&combined[r], &combined[s]);
+                    active[r] = false;
+                    touched[s] = true;
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let mut rows: Vec<usize> = (0..parities.len())
+        .filter(|r| active[*r] && !combined[*r].is_empty())
+        .collect();
+    let used = holders.iter().filter(|holder| !holder.is_empty()).count();
+    if rows.len() > used + EXTRA_RELATIONS {
+        rows.sort_by_key(|r| parities[*r].len());
+        rows.truncate(used + EXTRA_RELATIONS);
+    }
+    rows
+}
+fn find_dependencies(relations: &[Relation], columns: usize) -> Vec<Vec<usize>> {
+    let mut parities: Vec<Vec<usize>> = relations
+        .iter()
+        .map(|relation| {
+            let mut parity = vec![false; columns];
+            for column in &relation.factors {
+                parity[*column] ^= true;
+            }
+            (0..columns).filter(|column| parity[*column]).collect()
+        })
+        .collect();
+    let mut combined: Vec<Vec<usize>> = (0..relations.len()).map(|r| vec![r]).collect();
+    let rows = reduce_matrix(&mut parities, &mut combined, columns);
+    let mut compact = vec![usize::MAX; columns];
+    let mut used = 0;
+    for r in &rows {
+        for column in &parities[*r] {
+            if compact[*column] == usize::MAX {
+                compact[*column] = used;
+                used += 1;
+            }
+        }
+    }
+    let words = rows.len().div_ceil(64);
+    let mut matrix = vec![vec![0u64; words]; used];
+    for (k, r) in rows.iter().enumerate() {
+        for column in &parities[*r] {
+            matrix[compact[*column]][k / 64] |= 1 << (k % 64);
+        }
+    }
+    let bit = |row: &[u64], k: usize| (row[k / 64] >> (k % 64)) & 1 == 1;
+    let mut pivots = Vec::new();
+    for k in 0..rows.len() {
+        let rank = pivots.len();
+        let Some(found) = (rank..used).find(|i| bit(&matrix[*i], k)) else {
+            continue;
+        };
+        matrix.swap(rank, found);
+        let pivot = std::mem::take(&mut matrix[rank]);
+        for (i, row) in matrix.iter_mut().enumerate() {
+            if i != rank && bit(row, k) {
+                for (word, pivot_word) in row.iter_mut().zip(&pivot) {
+                    *word ^= pivot_word;
+                }
+            }
+        }
+        matrix[rank] = pivot;
+        pivots.push(k);
+    }
+    let mut is_pivot = vec![false; rows.len()];
+    for k in &pivots {
+        is_pivot[*k] = true;
+    }
+    (0..rows.len())
+        .filter(|k| !is_pivot[*k])
+        .take(64)
+        .map(|free| {
+            let mut dependency = combined[rows[free]].clone();
+            for (i, k) in pivots.iter().enumerate() {
+                if bit(&matrix[i], free) {
+                    dependency = symmetric_difference(&dependency, &combined[rows[*k]]);
+                }
+            }
+            dependency
+        })
+        .filter(|dependency| !dependency.is_empty())
+        .collect()
+}
+pub fn siqs_find_factor(n: &BigUint) -> Option<BigUint> {
+    if n.is_even() {
+        return (*n > BigUint::from(2u32)).then(|| BigUint::from(2u32));
+    }
+    if *n < BigUint::from(9u32) || n.miller_rabin_test() {
+        return None;
+    }
+    for degree in 2..n.bits() as u32 {
+        let root = n.nth_root(degree);
+        if root.pow(degree) == *n {
+            return Some(root);
+        }
+        if root < BigUint::from(3u32) {
+            break;
+        }
+    }
+    match QuadraticSieve::new(n) {
+        Ok(mut sieve) => sieve.run(),
+        Err(divisor) => (divisor != *n).then_some(divisor),
+    }
+}
@@ -1,2 +1,15 @@
+pub mod arithmetic;
+pub mod certificate;
+pub mod counting;
+pub mod dlog;
+pub mod factor;
 pub mod graph;
+pub mod modint;
+pub mod modsqrt;
+pub mod modular;
+pub mod montgomery;
 pub mod primality;
+pub mod primegen;
+pub mod primroot;
+pub mod sieve;
+pub mod special;
@@ -0,0 +1,265 @@
+use super::modular::{Modular, add_mod, sub_mod};
+use super::primality::PowMod;
+use num::{Integer, One, Zero};
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+// num::traits::{Zero, One} build values from nothing, but a ModInt needs its runtime modulus,
+// so it offers zero(modulus) and one(modulus) instead. Mod<M> implements both traits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ModInt<T> {
+    value: T,
+    modulus: T,
+}
+impl<T> ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    pub fn new(value: T, modulus: T) -> Self {
+        assert!(modulus > T::zero(), "modulus must be positive");
+        ModInt {
+            value: value.mod_floor(&modulus),
+            modulus,
+        }
+    }
+    pub fn zero(modulus: T) -> Self {
+        ModInt::new(T::zero(), modulus)
+    }
+    pub fn one(modulus: T) -> Self {
+        ModInt::new(T::one(), modulus)
+    }
+    pub fn value(&self) -> T {
+        self.value.clone()
+    }
+    pub fn modulus(&self) -> T {
+        self.modulus.clone()
+    }
+    pub fn pow(&self, exponent: u128) -> Self {
+        let value = self
+            .value
+            .clone()
+            .pow_mod(exponent, self.modulus.clone())
+            .mod_floor(&self.modulus);
+        ModInt {
+            value,
+            modulus: self.modulus.clone(),
+        }
+    }
+    pub fn inverse(&self) -> Option<Self> {
+        let value = self.value.clone();
+        value.mod_inverse(self.modulus.clone()).map(|value| ModInt {
+            value,
+            modulus: self.modulus.clone(),
+        })
+    }
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+    fn operands(self, other: Self) -> (T, T, T) {
+        assert!(
+            self.modulus == other.modulus,
+            "operands have different moduli"
+        );
+        (self.value, other.value, self.modulus)
+    }
+}
+impl<T> Add for ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        let (a, b, modulus) = self.operands(other);
+        let value = add_mod(a, b, &modulus);
+        ModInt { value, modulus }
+    }
+}
+impl<T> Sub for ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        let (a, b, modulus) = self.operands(other);
+        let value = sub_mod(a, b, &modulus);
+        ModInt { value, modulus }
+    }
+}
+impl<T> Mul for ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        let (a, b, modulus) = self.operands(other);
+        let value = a.mul_mod(b, modulus.clone());
+        ModInt { value, modulus }
+    }
+}
+impl<T> Div for ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        let (a, b, modulus) = self.operands(other);
+        let divisor = ModInt {
+            value: b,
+            modulus: modulus.clone(),
+        };
+        let inverse = divisor.inverse().expect("divisor is not invertible");
+        ModInt { value: a, modulus }.mul(inverse)
+    }
+}
+impl<T> Neg for ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    type Output = Self;
+    fn neg(self) -> Self {
+        ModInt {
+            value: T::zero(),
+            modulus: self.modulus.clone(),
+        } - self
+    }
+}
+impl<T> AddAssign for ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    fn add_assign(&mut self, other: Self) {
+        *self = self.clone() + other;
+    }
+}
+impl<T> SubAssign for ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    fn sub_assign(&mut self, other: Self) {
+        *self = self.clone() - other;
+    }
+}
+impl<T> MulAssign for ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    fn mul_assign(&mut self, other: Self) {
+        *self = self.clone() * other;
+    }
+}
+impl<T> DivAssign for ModInt<T>
+where
+    T: Clone + Integer + PowMod + Modular,
+{
+    fn div_assign(&mut self, other: Self) {
+        *self = self.clone() / other;
+    }
+}
+impl<T> Display for ModInt<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Mod<const M: u64> {
+    value: u64,
+}
+impl<const M: u64> Mod<M> {
+    pub fn new(value: u64) -> Self {
+        const { assert!(M > 0, "modulus must be positive") };
+        Mod { value: value % M }
+    }
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+    pub const fn modulus() -> u64 {
+        M
+    }
+    pub fn pow(&self, exponent: u128) -> Self {
+        Mod::new(self.value.pow_mod(exponent, M))
+    }
+    pub fn inverse(&self) -> Option<Self> {
+        self.value.mod_inverse(M).map(|value| Mod { value })
+    }
+}
+impl<const M: u64> From<u64> for Mod<M> {
+    fn from(value: u64) -> Self {
+        Mod::new(value)
+    }
+}
+impl<const M: u64> Add for Mod<M> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Mod {
+            value: add_mod(self.value, other.value, &M),
+        }
+    }
+}
+impl<const M: u64> Sub for Mod<M> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Mod {
+            value: sub_mod(self.value, other.value, &M),
+        }
+    }
+}
+impl<const M: u64> Mul for Mod<M> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Mod {
+            value: self.value.mul_mod(other.value, M),
+        }
+    }
+}
+impl<const M: u64> Div for Mod<M> {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        self.mul(other.inverse().expect("divisor is not invertible"))
+    }
+}
+impl<const M: u64> Neg for Mod<M> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Mod::zero() - self
+    }
+}
+impl<const M: u64> AddAssign for Mod<M> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+impl<const M: u64> SubAssign for Mod<M> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+impl<const M: u64> MulAssign for Mod<M> {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+impl<const M: u64> DivAssign for Mod<M> {
+    fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+    }
+}
+impl<const M: u64> Zero for Mod<M> {
+    fn zero() -> Self {
+        Mod::new(0)
+    }
+    fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+}
+impl<const M: u64> One for Mod<M> {
+    fn one() -> Self {
+        Mod::new(1)
+    }
+}
+impl<const M: u64> Display for Mod<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
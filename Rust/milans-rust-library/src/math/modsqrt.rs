@@ -0,0 +1,339 @@
+use super::factor::Factorize;
+use super::modular::{Modular, add_mod, power, sub_mod};
+use super::primality::PowMod;
+use num::{FromPrimitive, Integer, ToPrimitive, Zero};
+use num_bigint::BigUint;
+fn is_non_residue<T>(a: T, prime: &T) -> bool
+where
+    T: Clone + Integer + PowMod + ToPrimitive,
+{
+    let two = T::one() + T::one();
+    let half = (prime.clone() - T::one()) / two;
+    power(a, half, prime) == prime.clone() - T::one()
+}
+// Both searches are bounded, so a composite modulus gives None instead of looping, and a
+// returned root always squares to a.
+fn tonelli_shanks<T>(a: T, prime: T) -> Option<T>
+where
+    T: Clone + Integer + PowMod + ToPrimitive,
+{
+    assert!(prime > T::one(), "modulus must be prime");
+    let (one, two) = (T::one(), T::one() + T::one());
+    let a = a.mod_floor(&prime);
+    if a.is_zero() || prime == two {
+        return Some(a);
+    }
+    if is_non_residue(a.clone(), &prime) {
+        return None;
+    }
+    let (mut q, mut s) = (prime.clone() - one.clone(), 0);
+    while q.is_even() {
+        q = q / two.clone();
+        s += 1;
+    }
+    if s == 1 {
+        let root = power(a.clone(), q / two + one, &prime);
+        return (root.clone().mul_mod(root.clone(), prime) == a).then_some(root);
+    }
+    let mut z = two.clone();
+    while !is_non_residue(z.clone(), &prime) {
+        z = z + one.clone();
+        if z == prime {
+            return None;
+        }
+    }
+    let mut m = s;
+    let mut c = power(z, q.clone(), &prime);
+    let mut t = power(a.clone(), q.clone(), &prime);
+    let mut r = power(a, q / two + one.clone(), &prime);
+    while t != one {
+        let mut i = 0;
+        let mut square = t.clone();
+        while square != one {
+            square = square.clone().mul_mod(square, prime.clone());
+            i += 1;
+            if i == m {
+                return None;
+            }
+        }
+        let mut b = c;
+        for _ in 0..m - i - 1 {
+            b = b.clone().mul_mod(b, prime.clone());
+        }
+        m = i;
+        c = b.clone().mul_mod(b.clone(), prime.clone());
+        t = t.mul_mod(c.clone(), prime.clone());
+        r = r.mul_mod(b, prime.clone());
+    }
+    Some(r)
+}
+fn cipolla<T>(a: T, prime: T) -> Option<T>
+where
+    T: Clone + Integer + PowMod + ToPrimitive,
+{
+    assert!(prime > T::one(), "modulus must be prime");
+    let (one, two) = (T::one(), T::one() + T::one());
+    let a = a.mod_floor(&prime);
+    if a.is_zero() || prime == two {
+        return Some(a);
+    }
+    if is_non_residue(a.clone(), &prime) {
+        return None;
+    }
+    let mut t = T::zero();
+    let w = loop {
+        let w = sub_mod(
+            t.clone().mul_mod(t.clone(), prime.clone()),
+            a.clone(),
+            &prime,
+        );
+        if is_non_residue(w.clone(), &prime) {
+            break w;
+        }
+        t = t + one.clone();
+        if t == prime {
+            return None;
+        }
+    };
+    let multiply = |(x1, y1): (T, T), (x2, y2): (T, T)| {
+        let real = add_mod(
+            x1.clone().mul_mod(x2.clone(), prime.clone()),
+            y1.clone()
+                .mul_mod(y2.clone(), prime.clone())
+                .mul_mod(w.clone(), prime.clone()),
+            &prime,
+        );
+        let imaginary = add_mod(
+            x1.mul_mod(y2, prime.clone()),
+            x2.mul_mod(y1, prime.clone()),
+            &prime,
+        );
+        (real, imaginary)
+    };
+    let mut exponent = prime.clone() / two.clone() + one.clone();
+    let mut base = (t, one.clone());
+    let mut result = (one, T::zero());
+    while !exponent.is_zero() {
+        if exponent.is_odd() {
+            result = multiply(result, base.clone());
+        }
+        base = multiply(base.clone(), base);
+        exponent = exponent / two.clone();
+    }
+    let root = result.0;
+    (root.clone().mul_mod(root.clone(), prime) == a).then_some(root)
+}
+fn unit_roots<T>(a: T, prime: T, exponent: u32) -> Vec<T>
+where
+    T: Clone + Integer + PowMod + ToPrimitive + FromPrimitive + Modular,
+{
+    let (one, two) = (T::one(), T::one() + T::one());
+    let modulus = num::pow(prime.clone(), exponent as usize);
+    if prime == two {
+        let eight = T::from_u8(8).unwrap();
+        return match exponent {
+            1 => vec![one],
+            2 if a.mod_floor(&modulus).is_one() => vec![one.clone(), modulus - one],
+            2 => Vec::new(),
+            _ if !a.mod_floor(&eight).is_one() => Vec::new(),
+            _ => {
+                let mut root = one;
+                for bits in 3..exponent {
+                    let next = num::pow(two.clone(), bits as usize + 1);
+                    let square = root.clone().mul_mod(root.clone(), next.clone());
+                    if square != a.mod_floor(&next) {
+                        root = root + num::pow(two.clone(), bits as usize - 1);
+                    }
+                }
+                let half = modulus.clone() / two;
+                let other = add_mod(root.clone(), half, &modulus);
+                vec![
+                    root.clone(),
+                    modulus.clone() - root,
+                    other.clone(),
+                    modulus - other,
+                ]
+            }
+        };
+    }
+    let Some(mut root) = tonelli_shanks(a.clone(), prime.clone()) else {
+        return Vec::new();
+    };
+    let mut current = prime;
+    while current != modulus {
+        current = if current > modulus.clone() / current.clone() {
+            modulus.clone()
+        } else {
+            current.clone() * current
+        };
+        let error = sub_mod(
+            root.clone().mul_mod(root.clone(), current.clone()),
+            a.mod_floor(&current),
+            &current,
+        );
+        let inverse = add_mod(root.clone(), root.clone(), &current)
+            .mod_inverse(current.clone())
+            .unwrap();
+        root = sub_mod(root, error.mul_mod(inverse, current.clone()), &current);
+    }
+    vec![root.clone(), modulus - root]
+}
+struct Component<T> {
+    roots: Vec<T>,
+    step: T,
+    repeats: T,
+    coefficient: T,
+}
+fn prime_power_component<T>(a: T, prime: T, exponent: u32) -> Component<T>
+where
+    T: Clone + Integer + PowMod + ToPrimitive + FromPrimitive + Modular,
+{
+    let prime_power = |exponent: u32| num::pow(prime.clone(), exponent as usize);
+    let modulus = prime_power(exponent);
+    let mut a = a.mod_floor(&modulus);
+    if a.is_zero() {
+        return Component {
+            roots: vec![T::zero()],
+            step: prime_power(exponent.div_ceil(2)),
+            repeats: prime_power(exponent / 2),
+            coefficient: T::zero(),
+        };
+    }
+    let mut valuation = 0;
+    while a.is_multiple_of(&prime) {
+        a = a / prime.clone();
+        valuation += 1;
+    }
+    let roots = if valuation % 2 == 1 {
+        Vec::new()
+    } else {
+        let scale = prime_power(valuation / 2);
+        unit_roots(a, prime.clone(), exponent - valuation)
+            .into_iter()
+            .map(|root| root * scale.clone())
+            .collect()
+    };
+    Component {
+        roots,
+        step: prime_power(exponent - valuation / 2),
+        repeats: prime_power(valuation / 2),
+        coefficient: T::zero(),
+    }
+}
+pub struct SquareRoots<T> {
+    modulus: T,
+    components: Vec<Component<T>>,
+    positions: Vec<(usize, T)>,
+    finished: bool,
+}
+impl<T> SquareRoots<T>
+where
+    T: Clone + Integer + PowMod + ToPrimitive + FromPrimitive + Modular,
+{
+    fn new(modulus: T, components: Vec<(T, Component<T>)>) -> Self {
+        let finished = components
+            .iter()
+            .any(|(_, component)| component.roots.is_empty());
+        let components = components
+            .into_iter()
+            .map(|(prime_power, component)| {
+                let cofactor = modulus.clone() / prime_power.clone();
+                let inverse = cofactor
+                    .clone()
+                    .mod_floor(&prime_power)
+                    .mod_inverse(prime_power)
+                    .unwrap_or_else(T::zero);
+                Component {
+                    coefficient: cofactor.mul_mod(inverse, modulus.clone()),
+                    ..component
+                }
+            })
+            .collect::<Vec<_>>();
+        let positions = vec![(0, T::zero()); components.len()];
+        SquareRoots {
+            modulus,
+            components,
+            positions,
+            finished,
+        }
+    }
+    pub fn modulus(&self) -> T {
+        self.modulus.clone()
+    }
+}
+impl<T> Iterator for SquareRoots<T>
+where
+    T: Clone + Integer + PowMod + ToPrimitive + FromPrimitive + Modular,
+{
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        if self.finished {
+            return None;
+        }
+        let mut root = T::zero();
+        for (component, (index, repeat)) in self.components.iter().zip(&self.positions) {
+            let local = component.roots[*index].clone() + component.step.clone() * repeat.clone();
+            let term = local.mul_mod(component.coefficient.clone(), self.modulus.clone());
+            root = add_mod(root, term, &self.modulus);
+        }
+        self.finished = true;
+        for (component, (index, repeat)) in self.components.iter().zip(&mut self.positions) {
+            *repeat = repeat.clone() + T::one();
+            if *repeat != component.repeats {
+                self.finished = false;
+                break;
+            }
+            *repeat = T::zero();
+            *index += 1;
+            if *index != component.roots.len() {
+                self.finished = false;
+                break;
+            }
+            *index = 0;
+        }
+        Some(root)
+    }
+}
+pub trait SqrtMod: Sized {
+    fn tonelli_shanks(self, prime: Self) -> Option<Self>;
+    fn cipolla(self, prime: Self) -> Option<Self>;
+    fn sqrt_mod_prime_power(self, prime: Self, exponent: u32) -> SquareRoots<Self>;
+    fn sqrt_mod(self, modulus: Self) -> SquareRoots<Self>;
+}
+macro_rules! sqrt_mod {
+    ($type:ty) => {
+        impl SqrtMod for $type {
+            fn tonelli_shanks(self, prime: Self) -> Option<Self> {
+                tonelli_shanks(self, prime)
+            }
+            fn cipolla(self, prime: Self) -> Option<Self> {
+                cipolla(self, prime)
+            }
+            fn sqrt_mod_prime_power(self, prime: Self, exponent: u32) -> SquareRoots<Self> {
+                let modulus = num::pow(prime.clone(), exponent as usize);
+                let component = prime_power_component(self, prime, exponent);
+                SquareRoots::new(modulus.clone(), vec![(modulus, component)])
+            }
+            fn sqrt_mod(self, modulus: Self) -> SquareRoots<Self> {
+                assert!(modulus > Self::zero(), "modulus must be positive");
+                let components = modulus
+                    .clone()
+                    .factorize()
+                    .into_iter()
+                    .map(|(prime, exponent)| {
+                        let prime_power = num::pow(prime.clone(), exponent as usize);
+                        let component = prime_power_component(self.clone(), prime, exponent);
+                        (prime_power, component)
+                    })
+                    .collect();
+                SquareRoots::new(modulus, components)
+            }
+        }
+    };
+    ($($type:ty),+) => {
+        $(sqrt_mod!($type);)+
+    };
+}
+sqrt_mod!(
+    u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, BigUint
+);
@@ -0,0 +1,489 @@
+use super::primality::PowMod;
+use num::{Integer, One, Signed, ToPrimitive, Zero};
+use num_bigint::{BigInt, BigUint};
+pub(crate) fn add_mod<T>(a: T, b: T, modulus: &T) -> T
+where
+    T: Clone + Integer,
+{
+    let complement = modulus.clone() - b.clone();
+    if a >= complement {
+        a - complement
+    } else {
+        a + b
+    }
+}
+pub(crate) fn sub_mod<T>(a: T, b: T, modulus: &T) -> T
+where
+    T: Clone + Integer,
+{
+    if a >= b {
+        a - b
+    } else {
+        a + (modulus.clone() - b)
+    }
+}
+pub(crate) fn power<T>(base: T, exponent: T, modulus: &T) -> T
+where
+    T: Clone + Integer + PowMod + ToPrimitive,
+{
+    if let Some(exponent) = exponent.to_u128() {
+        return base.pow_mod(exponent, modulus.clone()).mod_floor(modulus);
+    }
+    let two = T::one() + T::one();
+    let (mut base, mut exponent) = (base.mod_floor(modulus), exponent);
+    let mut result = T::one();
+    while !exponent.is_zero() {
+        if exponent.is_odd() {
+            result = result.mul_mod(base.clone(), modulus.clone());
+        }
+        base = base.clone().mul_mod(base, modulus.clone());
+        exponent = exponent / two.clone();
+    }
+    result
+}
+pub trait Modular: Sized {
+    type Coefficient;
+    fn binary_gcd(self, other: Self) -> Self;
+    fn egcd(self, other: Self) -> (Self, Self::Coefficient, Self::Coefficient);
+    fn mod_inverse(self, modulus: Self) -> Option<Self>;
+    fn crt(congruences: &[(Self, Self)]) -> Option<(Self, Self)>;
+}
+macro_rules! modular_unsigned {
+    ($type:ty,$signed:ty) => {
+        impl Modular for $type {
+            type Coefficient = $signed;
+            fn binary_gcd(self, other: Self) -> Self {
+                let (mut a, mut b) = (self, other);
+                if a == 0 || b == 0 {
+                    return a | b;
+                }
+                let shift = (a | b).trailing_zeros();
+                a >>= a.trailing_zeros();
+                loop {
+                    b >>= b.trailing_zeros();
+                    if a > b {
+                        std::mem::swap(&mut a, &mut b);
+                    }
+                    b -= a;
+                    if b == 0 {
+                        return a << shift;
+                    }
+                }
+            }
+            fn egcd(self, other: Self) -> (Self, $signed, $signed) {
+                let (mut r0, mut r1) = (self, other);
+                let (mut x0, mut x1): ($signed, $signed) = (1, 0);
+                let (mut y0, mut y1): ($signed, $signed) = (0, 1);
+                while r1 != 0 {
+                    let quotient = r0 / r1;
+                    let step = quotient as $signed;
+                    (r0, r1) = (r1, r0 - quotient * r1);
+                    (x0, x1) = (x1, x0.wrapping_sub(step.wrapping_mul(x1)));
+                    (y0, y1) = (y1, y0.wrapping_sub(step.wrapping_mul(y1)));
+                }
+                (r0, x0, y0)
+            }
+            fn mod_inverse(self, modulus: Self) -> Option<Self> {
+                if modulus == 0 {
+                    return None;
+                }
+                let (gcd, x, _) = (self % modulus).egcd(modulus);
+                if gcd != 1 {
+                    None
+                } else if x < 0 {
+                    Some(modulus - x.unsigned_abs())
+                } else {
+                    Some(x as $type)
+                }
+            }
+            fn crt(congruences: &[(Self, Self)]) -> Option<(Self, Self)> {
+                let (mut x, mut lcm): (Self, Self) = (0, 1);
+                for (residue, modulus) in congruences.iter().copied() {
+                    if modulus == 0 {
+                        return None;
+                    }
+                    let residue = residue % modulus;
+                    let gcd = lcm.binary_gcd(modulus);
+                    let current = x % modulus;
+                    let difference = if residue >= current {
+                        residue - current
+                    } else {
+                        residue + (modulus - current)
+                    };
+                    if difference % gcd != 0 {
+                        return None;
+                    }
+                    let reduced = modulus / gcd;
+                    let inverse = (lcm / gcd % reduced).mod_inverse(reduced)?;
+                    let t = (difference / gcd).mul_mod(inverse, reduced);
+                    let next = lcm.checked_mul(reduced)?;
+                    x += lcm * t;
+                    lcm = next;
+                }
+                Some((x, lcm))
+            }
+        }
+    };
+    ($($type:ty,$signed:ty);+) => {
+        $(modular_unsigned!($type,$signed);)+
+    };
+}
+macro_rules! modular_signed {
+    ($type:ty,$unsigned:ty) => {
+        impl Modular for $type {
+            type Coefficient = $type;
+            // The gcd is never negative. For (MIN, 0) and (MIN, MIN) it is 2^(BITS - 1),
+            // which does not fit in the signed type, so those inputs panic.
+            fn binary_gcd(self, other: Self) -> Self {
+                let gcd = self.unsigned_abs().binary_gcd(other.unsigned_abs());
+                <$type>::try_from(gcd).expect("gcd overflows the signed type")
+            }
+            fn egcd(self, other: Self) -> (Self, Self, Self) {
+                let (gcd, x, y) = self.unsigned_abs().egcd(other.unsigned_abs());
+                let gcd = <$type>::try_from(gcd).expect("gcd overflows the signed type");
+                (gcd, x * self.signum(), y * other.signum())
+            }
+            fn mod_inverse(self, modulus: Self) -> Option<Self> {
+                if modulus <= 0 {
+                    return None;
+                }
+                let value = self.rem_euclid(modulus) as $unsigned;
+                value
+                    .mod_inverse(modulus as $unsigned)
+                    .map(|inverse| inverse as $type)
+            }
+            fn crt(congruences: &[(Self, Self)]) -> Option<(Self, Self)> {
+                let congruences = congruences
+                    .iter()
+                    .map(|(residue, modulus)| {
+                        (*modulus > 0)
+                            .then(|| (residue.rem_euclid(*modulus) as $unsigned, *modulus as $unsigned))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                let (x, lcm) = <$unsigned>::crt(&congruences)?;
+                Some((<$type>::try_from(x).ok()?, <$type>::try_from(lcm).ok()?))
+            }
+        }
+    };
+    ($($type:ty,$unsigned:ty);+) => {
+        $(modular_signed!($type,$unsigned);)+
+    };
+}
+modular_unsigned!(u8,i8;u16,i16;u32,i32;u64,i64;u128,i128;usize,isize);
+modular_signed!(i8,u8;i16,u16;i32,u32;i64,u64;i128,u128;isize,usize);
+fn big_binary_gcd(a: BigUint, b: BigUint) -> BigUint {
+    let (mut a, mut b) = (a, b);
+    let (Some(a_zeros), Some(b_zeros)) = (a.trailing_zeros(), b.trailing_zeros()) else {
+        return a | b;
+    };
+    let shift = a_zeros.min(b_zeros);
+    a >>= a_zeros;
+    loop {
+        b >>= b.trailing_zeros().unwrap();
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        b -= &a;
+        if b.is_zero() {
+            return a << shift;
+        }
+    }
+}
+impl Modular for BigInt {
+    type Coefficient = BigInt;
+    fn binary_gcd(self, other: Self) -> Self {
+        big_binary_gcd(self.magnitude().clone(), other.magnitude().clone()).into()
+    }
+    fn egcd(self, other: Self) -> (Self, Self, Self) {
+        let (mut r0, mut r1) = (self.abs(), other.abs());
+        let (mut x0, mut x1) = (BigInt::one(), BigInt::zero());
+        let (mut y0, mut y1) = (BigInt::zero(), BigInt::one());
+        while !r1.is_zero() {
+            let (quotient, remainder) = r0.div_rem(&r1);
+            (r0, r1) = (r1, remainder);
+            let x = x0 - &quotient * &x1;
+            (x0, x1) = (x1, x);
+            let y = y0 - &quotient * &y1;
+            (y0, y1) = (y1, y);
+        }
+        (r0, x0 * self.signum(), y0 * other.signum())
+    }
+    fn mod_inverse(self, modulus: Self) -> Option<Self> {
+        if !modulus.is_positive() {
+            return None;
+        }
+        let (gcd, x, _) = self.mod_floor(&modulus).egcd(modulus.clone());
+        gcd.is_one().then(|| x.mod_floor(&modulus))
+    }
+    fn crt(congruences: &[(Self, Self)]) -> Option<(Self, Self)> {
+        let (mut x, mut lcm) = (BigInt::zero(), BigInt::one());
+        for (residue, modulus) in congruences {
+            if !modulus.is_positive() {
+                return None;
+            }
+            let gcd = lcm.clone().binary_gcd(modulus.clone());
+            let difference = residue - &x;
+            if !(&difference % &gcd).is_zero() {
+                return None;
+            }
+            let reduced = modulus / &gcd;
+            let inverse = (&lcm / &gcd).mod_inverse(reduced.clone())?;
+            let t = (difference / &gcd * inverse).mod_floor(&reduced);
+            x += &lcm * t;
+            lcm *= reduced;
+        }
+        Some((x, lcm))
+    }
+}
+impl Modular for BigUint {
+    type Coefficient = BigInt;
+    fn binary_gcd(self, other: Self) -> Self {
+        big_binary_gcd(self, other)
+    }
+    fn egcd(self, other: Self) -> (Self, BigInt, BigInt) {
+        let (gcd, x, y) = BigInt::from(self).egcd(BigInt::from(other));
+        (gcd.magnitude().clone(), x, y)
+    }
+    fn mod_inverse(self, modulus: Self) -> Option<Self> {
+        BigInt::from(self)
+            .mod_inverse(BigInt::from(modulus))
+            .map(|inverse| inverse.magnitude().clone())
+    }
+    fn crt(congruences: &[(Self, Self)]) -> Option<(Self, Self)> {
+        let congruences: Vec<(BigInt, BigInt)> = congruences
+            .iter()
+            .map(|(residue, modulus)| (residue.clone().into(), modulus.clone().into()))
+            .collect();
+        let (x, lcm) = BigInt::crt(&congruences)?;
+        Some((x.magnitude().clone(), lcm.magnitude().clone()))
+    }
+}
+// jacobi and legendre are only defined for odd positive moduli and return None otherwise;
+// kronecker extends the symbol to every modulus.
+pub trait JacobiSymbol: Sized {
+    fn jacobi(self, n: Self) -> Option<i8>;
+    fn legendre(self, prime: Self) -> Option<i8>;
+    fn kronecker(self, n: Self) -> i8;
+}
+pub trait QuadraticResidue: Sized {
+    fn euler_criterion(self, prime: Self) -> i8;
+    fn quadratic_residue_test(self, prime: Self) -> bool;
+}
+fn odd_jacobi<T>(a: T, n: T) -> i8
+where
+    T: num::PrimInt,
+{
+    let (zero, one) = (T::zero(), T::one());
+    let (three, four, five, eight) = (
+        T::from(3).unwrap(),
+        T::from(4).unwrap(),
+        T::from(5).unwrap(),
+        T::from(8).unwrap(),
+    );
+    let mut a = a % n;
+    let mut n = n;
+    let mut result = 1;
+    while a != zero {
+        while a & one == zero {
+            a = a >> 1;
+            let rest = n % eight;
+            if rest == three || rest == five {
+                result = -result;
+            }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if a % four == three && n % four == three {
+            result = -result;
+        }
+        a = a % n;
+    }
+    if n == one { result } else { 0 }
+}
+fn kronecker_two(residue: u8, twos: u32) -> i8 {
+    if twos == 0 {
+        1
+    } else if residue & 1 == 0 {
+        0
+    } else if twos & 1 == 0 || residue == 1 || residue == 7 {
+        1
+    } else {
+        -1
+    }
+}
+macro_rules! symbols_unsigned {
+    ($type:ty) => {
+        impl JacobiSymbol for $type {
+            fn jacobi(self, n: Self) -> Option<i8> {
+                (n & 1 == 1).then(|| odd_jacobi(self, n))
+            }
+            fn legendre(self, prime: Self) -> Option<i8> {
+                self.jacobi(prime)
+            }
+            fn kronecker(self, n: Self) -> i8 {
+                if n == 0 {
+                    return (self == 1) as i8;
+                }
+                let twos = n.trailing_zeros();
+                kronecker_two((self % 8) as u8, twos) * odd_jacobi(self, n >> twos)
+            }
+        }
+        impl QuadraticResidue for $type {
+            fn euler_criterion(self, prime: Self) -> i8 {
+                let value = self % prime;
+                if value == 0 {
+                    0
+                } else if prime == 2 {
+                    1
+                } else {
+                    let power = value.pow_mod(((prime - 1) / 2) as u128, prime);
+                    if power == 1 {
+                        1
+                    } else if power == prime - 1 {
+                        -1
+                    } else {
+                        0
+                    }
+                }
+            }
+            fn quadratic_residue_test(self, prime: Self) -> bool {
+                self.euler_criterion(prime) >= 0
+            }
+        }
+    };
+    ($($type:ty),+) => {
+        $(symbols_unsigned!($type);)+
+    };
+}
+macro_rules! symbols_signed {
+    ($type:ty,$unsigned:ty) => {
+        impl JacobiSymbol for $type {
+            fn jacobi(self, n: Self) -> Option<i8> {
+                (n > 0 && n & 1 == 1)
+                    .then(|| odd_jacobi(self.rem_euclid(n) as $unsigned, n as $unsigned))
+            }
+            fn legendre(self, prime: Self) -> Option<i8> {
+                self.jacobi(prime)
+            }
+            fn kronecker(self, n: Self) -> i8 {
+                if n == 0 {
+                    return (self == 1 || self == -1) as i8;
+                }
+                let sign = if n < 0 && self < 0 { -1 } else { 1 };
+                let n = n.unsigned_abs();
+                let twos = n.trailing_zeros();
+                let odd = n >> twos;
+                let rest = self.unsigned_abs() % odd;
+                let value = if self < 0 && rest != 0 { odd - rest } else { rest };
+                sign * kronecker_two(self.rem_euclid(8) as u8, twos) * odd_jacobi(value, odd)
+            }
+        }
+        impl QuadraticResidue for $type {
+            fn euler_criterion(self, prime: Self) -> i8 {
+                assert!(prime > 0, "modulus must be positive");
+                (self.rem_euclid(prime) as $unsigned).euler_criterion(prime as $unsigned)
+            }
+            fn quadratic_residue_test(self, prime: Self) -> bool {
+                self.euler_criterion(prime) >= 0
+            }
+        }
+    };
+    ($($type:ty,$unsigned:ty);+) => {
+        $(symbols_signed!($type,$unsigned);)+
+    };
+}
+symbols_unsigned!(u8, u16, u32, u64, u128, usize);
+symbols_signed!(i8,u8;i16,u16;i32,u32;i64,u64;i128,u128;isize,usize);
+fn big_odd_jacobi(a: &BigUint, n: &BigUint) -> i8 {
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut result = 1;
+    while !a.is_zero() {
+        let zeros = a.trailing_zeros().unwrap();
+        a >>= zeros;
+        let rest = (&n % 8u32).to_u8().unwrap();
+        if zeros & 1 == 1 && (rest == 3 || rest == 5) {
+            result = -result;
+        }
+        std::mem::swap(&mut a, &mut n);
+        if a.bit(0) && a.bit(1) && n.bit(1) {
+            result = -result;
+        }
+        a %= &n;
+    }
+    if n.is_one() { result } else { 0 }
+}
+impl JacobiSymbol for BigUint {
+    fn jacobi(self, n: Self) -> Option<i8> {
+        n.bit(0).then(|| big_odd_jacobi(&self, &n))
+    }
+    fn legendre(self, prime: Self) -> Option<i8> {
+        self.jacobi(prime)
+    }
+    fn kronecker(self, n: Self) -> i8 {
+        let Some(twos) = n.trailing_zeros() else {
+            return self.is_one() as i8;
+        };
+        let residue = (&self % 8u32).to_u8().unwrap();
+        kronecker_two(residue, twos as u32) * big_odd_jacobi(&self, &(n >> twos))
+    }
+}
+impl JacobiSymbol for BigInt {
+    fn jacobi(self, n: Self) -> Option<i8> {
+        (n.is_positive() && n.bit(0))
+            .then(|| big_odd_jacobi(self.mod_floor(&n).magnitude(), n.magnitude()))
+    }
+    fn legendre(self, prime: Self) -> Option<i8> {
+        self.jacobi(prime)
+    }
+    fn kronecker(self, n: Self) -> i8 {
+        let Some(twos) = n.trailing_zeros() else {
+            return self.abs().is_one() as i8;
+        };
+        let sign = if n.is_negative() && self.is_negative() {
+            -1
+        } else {
+            1
+        };
+        let odd = BigInt::from(n.magnitude() >> twos);
+        let residue = self.mod_floor(&BigInt::from(8)).to_u8().unwrap();
+        let value = self.mod_floor(&odd);
+        sign * kronecker_two(residue, twos as u32)
+            * big_odd_jacobi(value.magnitude(), odd.magnitude())
+    }
+}
+impl QuadraticResidue for BigUint {
+    fn euler_criterion(self, prime: Self) -> i8 {
+        let value = self % &prime;
+        if value.is_zero() {
+            return 0;
+        }
+        let one = BigUint::one();
+        if prime == BigUint::from(2u32) {
+            return 1;
+        }
+        let power = value.modpow(&((&prime - &one) >> 1), &prime);
+        if power.is_one() {
+            1
+        } else if power == prime - one {
+            -1
+        } else {
+            0
+        }
+    }
+    fn quadratic_residue_test(self, prime: Self) -> bool {
+        self.euler_criterion(prime) >= 0
+    }
+}
+impl QuadraticResidue for BigInt {
+    fn euler_criterion(self, prime: Self) -> i8 {
+        assert!(prime.is_positive(), "modulus must be positive");
+        let value = self.mod_floor(&prime);
+        value
+            .magnitude()
+            .clone()
+            .euler_criterion(prime.magnitude().clone())
+    }
+    fn quadratic_residue_test(self, prime: Self) -> bool {
+        self.euler_criterion(prime) >= 0
+    }
+}
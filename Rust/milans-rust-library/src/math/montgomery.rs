@@ -0,0 +1,163 @@
+use num::traits::{WrappingAdd, WrappingMul, WrappingSub};
+pub trait MontgomeryWord:
+    num::PrimInt + num::Unsigned + WrappingAdd + WrappingSub + WrappingMul + std::fmt::Debug
+{
+    fn mul_wide(self, other: Self) -> (Self, Self);
+    fn reduce_wide(high: Self, low: Self, modulus: Self) -> Self;
+}
+impl MontgomeryWord for u64 {
+    fn mul_wide(self, other: Self) -> (Self, Self) {
+        let product = self as u128 * other as u128;
+        ((product >> 64) as u64, product as u64)
+    }
+    fn reduce_wide(high: Self, low: Self, modulus: Self) -> Self {
+        ((((high as u128) << 64) | low as u128) % modulus as u128) as u64
+    }
+}
+impl MontgomeryWord for u128 {
+    fn mul_wide(self, other: Self) -> (Self, Self) {
+        let (a1, a0) = (self >> 64, self & u64::MAX as u128);
+        let (b1, b0) = (other >> 64, other & u64::MAX as u128);
+        let (middle, middle_carry) = (a1 * b0).overflowing_add(a0 * b1);
+        let (low, low_carry) = (a0 * b0).overflowing_add(middle << 64);
+        let high = a1 * b1 + (middle >> 64) + ((middle_carry as u128) << 64) + low_carry as u128;
+        (high, low)
+    }
+    fn reduce_wide(high: Self, low: Self, modulus: Self) -> Self {
+        let mut result = high % modulus;
+        for bit in (0..128).rev() {
+            let carry = result >> 127;
+            result = (result << 1) | ((low >> bit) & 1);
+            if carry == 1 || result >= modulus {
+                result = result.wrapping_sub(modulus);
+            }
+        }
+        result
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Montgomery<T> {
+    modulus: T,
+    inverse: T,
+    one: T,
+    r_squared: T,
+}
+impl<T> Montgomery<T>
+where
+    T: MontgomeryWord,
+{
+    pub fn new(modulus: T) -> Option<Self> {
+        if modulus & T::one() == T::zero() {
+            return None;
+        }
+        let two = T::one() + T::one();
+        // Newton iteration, every step doubles the number of correct low bits (3 -> 192).
+        let mut inverse = modulus;
+        for _ in 0..7 {
+            inverse = inverse.wrapping_mul(&two.wrapping_sub(&modulus.wrapping_mul(&inverse)));
+        }
+        let one = (T::max_value() % modulus + T::one()) % modulus;
+        let (high, low) = one.mul_wide(one);
+        let r_squared = T::reduce_wide(high, low, modulus);
+        Some(Montgomery {
+            modulus,
+            inverse,
+            one,
+            r_squared,
+        })
+    }
+    pub fn modulus(&self) -> T {
+        self.modulus
+    }
+    pub fn inverse(&self) -> T {
+        self.inverse
+    }
+    pub fn one(&self) -> T {
+        self.one
+    }
+    pub fn minus_one(&self) -> T {
+        self.neg(self.one)
+    }
+    fn reduce(&self, high: T, low: T) -> T {
+        let (m_high, _) = low.wrapping_mul(&self.inverse).mul_wide(self.modulus);
+        if high < m_high {
+            high.wrapping_sub(&m_high).wrapping_add(&self.modulus)
+        } else {
+            high - m_high
+        }
+    }
+    pub fn to_montgomery(&self, value: T) -> T {
+        let (high, low) = (value % self.modulus).mul_wide(self.r_squared);
+        self.reduce(high, low)
+    }
+    pub fn from_montgomery(&self, value: T) -> T {
+        self.reduce(T::zero(), value)
+    }
+    pub fn mul(&self, a: T, b: T) -> T {
+        let (high, low) = a.mul_wide(b);
+        self.reduce(high, low)
+    }
+    pub fn square(&self, a: T) -> T {
+        self.mul(a, a)
+    }
+    pub fn add(&self, a: T, b: T) -> T {
+        if a >= self.modulus - b {
+            a - (self.modulus - b)
+        } else {
+            a + b
+        }
+    }
+    pub fn sub(&self, a: T, b: T) -> T {
+        if a >= b {
+            a - b
+        } else {
+            a + (self.modulus - b)
+        }
+    }
+    pub fn neg(&self, a: T) -> T {
+        if a == T::zero() { a } else { self.modulus - a }
+    }
+    pub fn pow(&self, base: T, exponent: u128) -> T {
+        let mut base = base;
+        let mut exponent = exponent;
+        let mut result = self.one;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = self.mul(result, base);
+            }
+            base = self.square(base);
+            exponent >>= 1;
+        }
+        result
+    }
+    pub fn mul_mod(&self, a: T, b: T) -> T {
+        let (high, low) = (a % self.modulus).mul_wide(b % self.modulus);
+        let (high, low) = self.reduce(high, low).mul_wide(self.r_squared);
+        self.reduce(high, low)
+    }
+    pub fn pow_mod(&self, base: T, exponent: u128) -> T {
+        self.from_montgomery(self.pow(self.to_montgomery(base), exponent))
+    }
+    pub fn is_witness(&self, base: T, exponent: u128, squares: u32) -> bool {
+        let minus_one = self.minus_one();
+        let mut result = self.pow(self.to_montgomery(base), exponent);
+        if result == T::zero() {
+            true
+        } else if result == self.one || result == minus_one {
+            false
+        } else {
+            let mut is_witness = true;
+            for _ in 0..squares {
+                result = self.square(result);
+                if result == T::zero() || result == self.one {
+                    break;
+                }
+                if result == minus_one {
+                    is_witness = false;
+                    break;
+                }
+            }
+            is_witness
+        }
+    }
+}
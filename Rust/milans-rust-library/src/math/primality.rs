@@ -1,3 +1,6 @@
+use super::modular::JacobiSymbol;
+use super::montgomery::{Montgomery, MontgomeryWord};
+use milans_rust_core::random::{Rng, SplitMix64};
 macro_rules! pow_mod {
     ($type1:ty,$type2:ty) => {
         impl PowMod for $type1 {
@@ -23,16 +26,15 @@ pub trait PowMod {
     fn mul_mod(self, other: Self, modulus: Self) -> Self;
     fn pow_mod(self, exponent: u128, modulus: Self) -> Self;
 }
-pow_mod!(u8,u16;i8,i16;u16,u32;i16,i32;u32,u64;i32,i64;u64,u128;i64,i128;usize,u128;isize,i128);
-impl PowMod for u128 {
+pow_mod!(u8,u16;i8,i16;u16,u32;i16,i32;u32,u64;i32,i64;i64,i128;isize,i128);
+impl PowMod for u64 {
     fn mul_mod(self, other: Self, modulus: Self) -> Self {
-        use num::ToPrimitive;
-        use num_bigint::ToBigInt;
-        ((self.to_bigint().unwrap() * other.to_bigint().unwrap()) % modulus.to_bigint().unwrap())
-            .to_u128()
-            .unwrap()
+        (self as u128 * other as u128 % modulus as u128) as u64
     }
-    fn pow_mod(self, exponent: u128, modulus: u128) -> u128 {
+    fn pow_mod(self, exponent: u128, modulus: u64) -> u64 {
+        if let Some(montgomery) = Montgomery::new(modulus) {
+            return montgomery.pow_mod(self, exponent);
+        }
         let mut base = self;
         let mut result = 1;
         for bit in 0..128 {
@@ -44,15 +46,26 @@ impl PowMod for u128 {
         result
     }
 }
-impl PowMod for i128 {
+impl PowMod for usize {
     fn mul_mod(self, other: Self, modulus: Self) -> Self {
-        use num::ToPrimitive;
-        use num_bigint::ToBigInt;
-        ((self.to_bigint().unwrap() * other.to_bigint().unwrap()) % modulus.to_bigint().unwrap())
-            .to_i128()
-            .unwrap()
+        (self as u64).mul_mod(other as u64, modulus as u64) as usize
     }
-    fn pow_mod(self, exponent: u128, modulus: i128) -> i128 {
+    fn pow_mod(self, exponent: u128, modulus: usize) -> usize {
+        (self as u64).pow_mod(exponent, modulus as u64) as usize
+    }
+}
+impl PowMod for u128 {
+    fn mul_mod(self, other: Self, modulus: Self) -> Self {
+        if self <= u64::MAX as u128 && other <= u64::MAX as u128 {
+            return self * other % modulus;
+        }
+        let (high, low) = self.mul_wide(other);
+        u128::reduce_wide(high, low, modulus)
+    }
+    fn pow_mod(self, exponent: u128, modulus: u128) -> u128 {
+        if let Some(montgomery) = Montgomery::new(modulus) {
+            return montgomery.pow_mod(self, exponent);
+        }
         let mut base = self;
         let mut result = 1;
         for bit in 0..128 {
@@ -64,6 +77,28 @@ impl PowMod for i128 {
         result
     }
 }
+impl PowMod for i128 {
+    fn mul_mod(self, other: Self, modulus: Self) -> Self {
+        let result = self
+            .unsigned_abs()
+            .mul_mod(other.unsigned_abs(), modulus.unsigned_abs()) as i128;
+        if (self < 0) != (other < 0) {
+            -result
+        } else {
+            result
+        }
+    }
+    fn pow_mod(self, exponent: u128, modulus: i128) -> i128 {
+        let result = self
+            .unsigned_abs()
+            .pow_mod(exponent, modulus.unsigned_abs()) as i128;
+        if self < 0 && exponent & 1 == 1 {
+            -result
+        } else {
+            result
+        }
+    }
+}
 impl<T> PowMod for std::rc::Rc<T>
 where
     T: PowMod + Copy,
@@ -86,6 +121,34 @@ where
         std::sync::Arc::new(<T as PowMod>::pow_mod(*self, exponent, *modulus))
     }
 }
+impl PowMod for num_bigint::BigUint {
+    fn mul_mod(self, other: Self, modulus: Self) -> Self {
+        self * other % modulus
+    }
+    fn pow_mod(self, exponent: u128, modulus: Self) -> Self {
+        self.modpow(&num_bigint::BigUint::from(exponent), &modulus)
+    }
+}
+impl PowMod for num_bigint::BigInt {
+    fn mul_mod(self, other: Self, modulus: Self) -> Self {
+        self * other % modulus
+    }
+    fn pow_mod(self, exponent: u128, modulus: Self) -> Self {
+        use num::Signed;
+        use num_bigint::{BigInt, Sign};
+        let result = BigInt::from_biguint(
+            Sign::Plus,
+            self.magnitude()
+                .clone()
+                .pow_mod(exponent, modulus.magnitude().clone()),
+        );
+        if self.is_negative() && exponent & 1 == 1 {
+            -result
+        } else {
+            result
+        }
+    }
+}
 macro_rules! miller_rabin_is_witness {
     ($prime:expr,$base:expr,$exponent:expr,$squares:expr) => {{
         let prime = $prime;
@@ -113,6 +176,8 @@ macro_rules! miller_rabin_is_witness {
         }
     }};
 }
+// Jim Sinclair's base set, deterministic for every n < 2^64.
+const WITNESSES_U64: [u64; 7] = [2, 325, 9375, 28178, 450775, 9780504, 1795265022];
 pub trait MillerRabinTest {
     fn miller_rabin_test(self) -> bool;
     fn miller_rabin_test_iter(self, iterations: usize) -> bool;
@@ -123,7 +188,7 @@ impl MillerRabinTest for u8 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -139,7 +204,7 @@ impl MillerRabinTest for u8 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -160,7 +225,7 @@ impl MillerRabinTest for i8 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -176,7 +241,7 @@ impl MillerRabinTest for i8 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -197,7 +262,7 @@ impl MillerRabinTest for u16 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -219,7 +284,7 @@ impl MillerRabinTest for u16 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -246,7 +311,7 @@ impl MillerRabinTest for i16 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -268,7 +333,7 @@ impl MillerRabinTest for i16 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -295,7 +360,7 @@ impl MillerRabinTest for u32 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -319,7 +384,7 @@ impl MillerRabinTest for u32 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -352,7 +417,7 @@ impl MillerRabinTest for i32 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -376,7 +441,7 @@ impl MillerRabinTest for i32 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -409,7 +474,7 @@ impl MillerRabinTest for u64 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -430,8 +495,9 @@ impl MillerRabinTest for u64 {
             }
             return true;
         }
-        for a in (3..=43).step_by(2) {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
+        let montgomery = Montgomery::new(prime).unwrap();
+        for a in &WITNESSES_U64[1..] {
+            if montgomery.is_witness(*a, exponent as u128, squares) {
                 return false;
             }
         }
@@ -442,7 +508,7 @@ impl MillerRabinTest for u64 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -470,8 +536,9 @@ impl MillerRabinTest for u64 {
             }
             return true;
         }
-        for a in (3..=43).step_by(2).take(iterations - 1) {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
+        let montgomery = Montgomery::new(prime).unwrap();
+        for a in WITNESSES_U64[1..].iter().take(iterations - 1) {
+            if montgomery.is_witness(*a, exponent as u128, squares) {
                 return false;
             }
         }
@@ -484,7 +551,7 @@ impl MillerRabinTest for i64 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -505,8 +572,9 @@ impl MillerRabinTest for i64 {
             }
             return true;
         }
-        for a in (3..=43).step_by(2) {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
+        let montgomery = Montgomery::new(prime as u64).unwrap();
+        for a in &WITNESSES_U64[1..] {
+            if montgomery.is_witness(*a, exponent as u128, squares) {
                 return false;
             }
         }
@@ -517,7 +585,7 @@ impl MillerRabinTest for i64 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -544,8 +612,9 @@ impl MillerRabinTest for i64 {
             }
             return true;
         }
-        for a in (3..=43).step_by(2) {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
+        let montgomery = Montgomery::new(prime as u64).unwrap();
+        for a in WITNESSES_U64[1..].iter().take(iterations - 1) {
+            if montgomery.is_witness(*a, exponent as u128, squares) {
                 return false;
             }
         }
@@ -558,7 +627,7 @@ impl MillerRabinTest for usize {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -579,8 +648,9 @@ impl MillerRabinTest for usize {
             }
             return true;
         }
-        for a in (3..=43).step_by(2) {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
+        let montgomery = Montgomery::new(prime as u64).unwrap();
+        for a in &WITNESSES_U64[1..] {
+            if montgomery.is_witness(*a, exponent as u128, squares) {
                 return false;
             }
         }
@@ -591,7 +661,7 @@ impl MillerRabinTest for usize {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -619,8 +689,9 @@ impl MillerRabinTest for usize {
             }
             return true;
         }
-        for a in (3..=43).step_by(2).take(iterations - 1) {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
+        let montgomery = Montgomery::new(prime as u64).unwrap();
+        for a in WITNESSES_U64[1..].iter().take(iterations - 1) {
+            if montgomery.is_witness(*a, exponent as u128, squares) {
                 return false;
             }
         }
@@ -633,7 +704,7 @@ impl MillerRabinTest for isize {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -654,8 +725,9 @@ impl MillerRabinTest for isize {
             }
             return true;
         }
-        for a in (3..=43).step_by(2) {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
+        let montgomery = Montgomery::new(prime as u64).unwrap();
+        for a in &WITNESSES_U64[1..] {
+            if montgomery.is_witness(*a, exponent as u128, squares) {
                 return false;
             }
         }
@@ -666,7 +738,7 @@ impl MillerRabinTest for isize {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -693,8 +765,9 @@ impl MillerRabinTest for isize {
             }
             return true;
         }
-        for a in (3..=43).step_by(2) {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
+        let montgomery = Montgomery::new(prime as u64).unwrap();
+        for a in WITNESSES_U64[1..].iter().take(iterations - 1) {
+            if montgomery.is_witness(*a, exponent as u128, squares) {
                 return false;
             }
         }
@@ -707,7 +780,7 @@ impl MillerRabinTest for u128 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -730,26 +803,22 @@ impl MillerRabinTest for u128 {
         }
         if prime <= u64::MAX as u128 {
             let prime = prime as u64;
-            for a in (3..=43).step_by(2) {
-                if miller_rabin_is_witness!(prime, a, exponent, squares) {
+            let montgomery = Montgomery::new(prime).unwrap();
+            for a in &WITNESSES_U64[1..] {
+                if montgomery.is_witness(*a, exponent, squares) {
                     return false;
                 }
             }
             return true;
         }
-        for a in 3..=2 * 128 * 128 {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
-                return false;
-            }
-        }
-        true
+        baillie_psw(prime)
     }
     fn miller_rabin_test_iter(self, iterations: usize) -> bool {
         let prime = self;
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -775,23 +844,19 @@ impl MillerRabinTest for u128 {
             if miller_rabin_is_witness!(prime, 61, exponent, squares) {
                 return false;
             }
-            return false;
+            return true;
         }
         if prime <= u64::MAX as u128 {
             let prime = prime as u64;
-            for a in (3..=43).step_by(2).take(iterations - 1) {
-                if miller_rabin_is_witness!(prime, a, exponent, squares) {
+            let montgomery = Montgomery::new(prime).unwrap();
+            for a in WITNESSES_U64[1..].iter().take(iterations - 1) {
+                if montgomery.is_witness(*a, exponent, squares) {
                     return false;
                 }
             }
             return true;
         }
-        for a in 3..=2 * 128 * 128 {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
-                return false;
-            }
-        }
-        true
+        baillie_psw(prime)
     }
 }
 impl MillerRabinTest for i128 {
@@ -800,7 +865,7 @@ impl MillerRabinTest for i128 {
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         let exponent = prime >> 1;
@@ -823,26 +888,22 @@ impl MillerRabinTest for i128 {
         }
         if prime <= u64::MAX as i128 {
             let prime = prime as u64;
-            for a in (3..=43).step_by(2) {
-                if miller_rabin_is_witness!(prime, a, exponent, squares) {
+            let montgomery = Montgomery::new(prime).unwrap();
+            for a in &WITNESSES_U64[1..] {
+                if montgomery.is_witness(*a, exponent as u128, squares) {
                     return false;
                 }
             }
             return true;
         }
-        for a in 3..=2 * 128 * 128 {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
-                return false;
-            }
-        }
-        true
+        baillie_psw(prime as u128)
     }
     fn miller_rabin_test_iter(self, iterations: usize) -> bool {
         let prime = self;
         if self < 2 {
             return false;
         }
-        if self % 6 != 1 && self % 6 != 3 {
+        if self % 6 != 1 && self % 6 != 5 {
             return prime < 4;
         }
         if iterations == 0 {
@@ -868,23 +929,341 @@ impl MillerRabinTest for i128 {
             if miller_rabin_is_witness!(prime, 61, exponent, squares) {
                 return false;
             }
-            return false;
+            return true;
         }
         if prime <= u64::MAX as i128 {
             let prime = prime as u64;
-            for a in (3..=43).step_by(2).take(iterations - 1) {
-                if miller_rabin_is_witness!(prime, a, exponent, squares) {
+            let montgomery = Montgomery::new(prime).unwrap();
+            for a in WITNESSES_U64[1..].iter().take(iterations - 1) {
+                if montgomery.is_witness(*a, exponent as u128, squares) {
                     return false;
                 }
             }
             return true;
         }
-        for a in 3..=2 * 128 * 128 {
-            if miller_rabin_is_witness!(prime, a, exponent, squares) {
-                return false;
+        baillie_psw(prime as u128)
+    }
+}
+const fn mul_mod_u64(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+pub const fn pow_mod_u64(base: u64, exponent: u128, modulus: u64) -> u64 {
+    let (mut base, mut exponent, mut result) = (base, exponent, 1 % modulus);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod_u64(result, base, modulus);
+        }
+        base = mul_mod_u64(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+const fn is_witness_const(prime: u64, base: u64, exponent: u64, squares: u32) -> bool {
+    let mut result = pow_mod_u64(base, exponent as u128, prime);
+    if result == 0 {
+        return true;
+    }
+    if result == 1 || result == prime - 1 {
+        return false;
+    }
+    let mut square = 0;
+    while square < squares {
+        result = mul_mod_u64(result, result, prime);
+        if result < 2 {
+            return true;
+        }
+        if result == prime - 1 {
+            return false;
+        }
+        square += 1;
+    }
+    true
+}
+const fn miller_rabin_const(prime: u64, bases: &[u64]) -> bool {
+    let exponent = prime >> 1;
+    let squares = exponent.trailing_zeros();
+    let exponent = exponent >> squares;
+    let mut index = 0;
+    while index < bases.len() {
+        if is_witness_const(prime, bases[index], exponent, squares) {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
+pub const fn is_prime_u64(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    if n % 6 != 1 && n % 6 != 5 {
+        return n < 4;
+    }
+    if n < 2047 {
+        return miller_rabin_const(n, &[2]);
+    }
+    if n <= u32::MAX as u64 {
+        return miller_rabin_const(n, &[2, 7, 61]);
+    }
+    miller_rabin_const(n, &WITNESSES_U64)
+}
+pub const fn is_prime_u32(n: u32) -> bool {
+    is_prime_u64(n as u64)
+}
+pub const fn next_prime_const(n: u64) -> u64 {
+    let mut candidate = if n < 2 { 2 } else { n };
+    while !is_prime_u64(candidate) {
+        assert!(candidate < u64::MAX, "no prime this large fits in u64");
+        candidate += 1;
+    }
+    candidate
+}
+const BATCH_LANES: usize = 4;
+const TRIAL_DIVISORS: [(u64, u64, u64); 53] = trial_divisors();
+const TRIAL_BOUND: u64 = next_prime_const(TRIAL_DIVISORS[TRIAL_DIVISORS.len() - 1].0 + 1);
+const fn trial_divisors() -> [(u64, u64, u64); 53] {
+    let mut divisors = [(0, 0, 0); 53];
+    let (mut index, mut prime) = (0, 3);
+    while index < divisors.len() {
+        if is_prime_u64(prime) {
+            // n is divisible by an odd p exactly when n * p^-1 mod 2^64 <= (2^64 - 1) / p.
+            let mut inverse = prime;
+            let mut step = 0;
+            while step < 6 {
+                inverse = inverse.wrapping_mul(2u64.wrapping_sub(prime.wrapping_mul(inverse)));
+                step += 1;
+            }
+            divisors[index] = (prime, inverse, u64::MAX / prime);
+            index += 1;
+        }
+        prime += 2;
+    }
+    divisors
+}
+fn batch_trial_division(n: u64) -> Option<bool> {
+    if n < 2 || n & 1 == 0 {
+        return Some(n == 2);
+    }
+    for (prime, inverse, limit) in TRIAL_DIVISORS {
+        if n.wrapping_mul(inverse) <= limit {
+            return Some(n == prime);
+        }
+    }
+    if n < TRIAL_BOUND * TRIAL_BOUND {
+        return Some(true);
+    }
+    None
+}
+#[derive(Clone, Copy)]
+struct BatchCandidate {
+    index: usize,
+    montgomery: Montgomery<u64>,
+    exponent: u64,
+    squares: u32,
+    bases: &'static [u64],
+}
+impl BatchCandidate {
+    fn new(index: usize, n: u64) -> Self {
+        let exponent = n >> 1;
+        let squares = exponent.trailing_zeros();
+        BatchCandidate {
+            index,
+            montgomery: Montgomery::new(n).unwrap(),
+            exponent: exponent >> squares,
+            squares,
+            bases: if n <= u32::MAX as u64 {
+                &[2, 7, 61]
+            } else {
+                &WITNESSES_U64
+            },
+        }
+    }
+}
+fn batch_witnesses(chunk: &[BatchCandidate], round: usize) -> [bool; BATCH_LANES] {
+    let lanes: [BatchCandidate; BATCH_LANES] =
+        std::array::from_fn(|lane| chunk[lane.min(chunk.len() - 1)]);
+    // Fixed two-bit windows keep every lane on the same branch-free schedule.
+    let table: [[u64; 4]; BATCH_LANES] = std::array::from_fn(|lane| {
+        let montgomery = &lanes[lane].montgomery;
+        let base = montgomery.to_montgomery(lanes[lane].bases[round]);
+        let square = montgomery.square(base);
+        [montgomery.one(), base, square, montgomery.mul(square, base)]
+    });
+    let mut result: [u64; BATCH_LANES] = std::array::from_fn(|lane| lanes[lane].montgomery.one());
+    let bits = lanes
+        .iter()
+        .map(|lane| u64::BITS - lane.exponent.leading_zeros())
+        .max()
+        .unwrap();
+    for shift in (0..bits.div_ceil(2)).rev().map(|digit| 2 * digit) {
+        for lane in 0..BATCH_LANES {
+            let montgomery = &lanes[lane].montgomery;
+            let digit = (lanes[lane].exponent >> shift & 3) as usize;
+            let square = montgomery.square(montgomery.square(result[lane]));
+            result[lane] = montgomery.mul(square, table[lane][digit]);
+        }
+    }
+    let mut decided: [Option<bool>; BATCH_LANES] = std::array::from_fn(|lane| {
+        let montgomery = &lanes[lane].montgomery;
+        if result[lane] == 0 {
+            Some(true)
+        } else if result[lane] == montgomery.one() || result[lane] == montgomery.minus_one() {
+            Some(false)
+        } else {
+            None
+        }
+    });
+    let squares = lanes.iter().map(|lane| lane.squares).max().unwrap();
+    for step in 0..squares {
+        for lane in 0..BATCH_LANES {
+            if decided[lane].is_some() || step >= lanes[lane].squares {
+                continue;
+            }
+            let montgomery = &lanes[lane].montgomery;
+            result[lane] = montgomery.square(result[lane]);
+            if result[lane] == montgomery.minus_one() {
+                decided[lane] = Some(false);
+            } else if result[lane] == 0 || result[lane] == montgomery.one() {
+                decided[lane] = Some(true);
             }
         }
+    }
+    decided.map(|decided| decided.unwrap_or(true))
+}
+fn prime_batch<T, F>(values: &[T], mut mark: F)
+where
+    T: Copy + num::ToPrimitive + MillerRabinTest,
+    F: FnMut(usize),
+{
+    let mut pending = Vec::new();
+    for (index, value) in values.iter().enumerate() {
+        match value.to_u64().map(batch_trial_division) {
+            Some(Some(true)) => mark(index),
+            Some(Some(false)) => {}
+            Some(None) => pending.push(BatchCandidate::new(index, value.to_u64().unwrap())),
+            None => {
+                if value.miller_rabin_test() {
+                    mark(index);
+                }
+            }
+        }
+    }
+    for round in 0..WITNESSES_U64.len() {
+        let mut survivors = Vec::with_capacity(pending.len());
+        for chunk in pending.chunks(BATCH_LANES) {
+            for (candidate, witness) in chunk.iter().zip(batch_witnesses(chunk, round)) {
+                if witness {
+                    continue;
+                }
+                if round + 1 == candidate.bases.len() {
+                    mark(candidate.index);
+                } else {
+                    survivors.push(*candidate);
+                }
+            }
+        }
+        pending = survivors;
+    }
+}
+pub fn is_prime_batch<T>(values: &[T]) -> Vec<bool>
+where
+    T: Copy + num::ToPrimitive + MillerRabinTest,
+{
+    let mut primes = vec![false; values.len()];
+    prime_batch(values, |index| primes[index] = true);
+    primes
+}
+pub fn is_prime_batch_bitset<T>(values: &[T]) -> Vec<u64>
+where
+    T: Copy + num::ToPrimitive + MillerRabinTest,
+{
+    let mut words = vec![0; values.len().div_ceil(64)];
+    prime_batch(values, |index| words[index / 64] |= 1 << (index % 64));
+    words
+}
+fn big_is_witness(
+    prime: &num_bigint::BigUint,
+    base: &num_bigint::BigUint,
+    exponent: &num_bigint::BigUint,
+    squares: u64,
+) -> bool {
+    use num::{One, Zero};
+    let minus_one = prime - 1u32;
+    let mut result = base.modpow(exponent, prime);
+    if result.is_zero() {
         true
+    } else if result.is_one() || result == minus_one {
+        false
+    } else {
+        let mut is_witness = true;
+        for _ in 0..squares {
+            result = &result * &result % prime;
+            if result.is_zero() || result.is_one() {
+                break;
+            }
+            if result == minus_one {
+                is_witness = false;
+                break;
+            }
+        }
+        is_witness
+    }
+}
+fn big_random_witness<R>(prime: &num_bigint::BigUint, rng: &mut R) -> num_bigint::BigUint
+where
+    R: Rng,
+{
+    use num_bigint::BigUint;
+    let range = prime - 3u32;
+    let bits = range.bits();
+    loop {
+        let mut candidate = BigUint::from(0u32);
+        for _ in 0..bits.div_ceil(64) {
+            candidate = (candidate << 64u32) + rng.next_u64();
+        }
+        candidate >>= bits.div_ceil(64) * 64 - bits;
+        if candidate < range {
+            return candidate + 2u32;
+        }
+    }
+}
+impl MillerRabinTest for num_bigint::BigUint {
+    // Above u128 this runs Baillie-PSW, since a fixed set of Miller-Rabin bases can be fooled by
+    // composites built for it. No composite is known to pass Baillie-PSW, but none is proven not
+    // to, so `true` means a probable prime.
+    fn miller_rabin_test(self) -> bool {
+        use num::ToPrimitive;
+        if let Some(prime) = self.to_u128() {
+            return prime.miller_rabin_test();
+        }
+        self.baillie_psw_test()
+    }
+    // Draws `iterations` uniform bases in [2, n - 2] from a generator seeded by n, so the answer
+    // is reproducible. Callers supplying their own RNG use miller_rabin_test_random.
+    fn miller_rabin_test_iter(self, iterations: usize) -> bool {
+        use num::{ToPrimitive, Zero};
+        if let Some(prime) = self.to_u128() {
+            return prime.miller_rabin_test_iter(iterations);
+        }
+        if SMALL_PRIMES.iter().any(|small| (&self % small).is_zero()) {
+            return false;
+        }
+        let mut rng = SplitMix64::new((&self % u64::MAX).to_u64().unwrap());
+        self.miller_rabin_test_random(&mut rng, iterations)
+    }
+}
+impl MillerRabinTest for num_bigint::BigInt {
+    fn miller_rabin_test(self) -> bool {
+        match self.to_biguint() {
+            Some(prime) => prime.miller_rabin_test(),
+            None => false,
+        }
+    }
+    fn miller_rabin_test_iter(self, iterations: usize) -> bool {
+        match self.to_biguint() {
+            Some(prime) => prime.miller_rabin_test_iter(iterations),
+            None => false,
+        }
     }
 }
 impl<T> MillerRabinTest for &T
@@ -920,3 +1299,811 @@ where
         <T as MillerRabinTest>::miller_rabin_test_iter((*self).clone(), iterations)
     }
 }
+fn fermat<T>(n: T, base: T) -> bool
+where
+    T: num::PrimInt + PowMod,
+{
+    if base == T::zero() {
+        return false;
+    }
+    base.pow_mod((n - T::one()).to_u128().unwrap(), n) == T::one()
+}
+fn solovay_strassen<T>(n: T, base: T) -> bool
+where
+    T: num::PrimInt + PowMod + JacobiSymbol,
+{
+    if n & T::one() == T::zero() {
+        return n == T::from(2).unwrap();
+    }
+    if base == T::zero() {
+        return false;
+    }
+    let expected = match base.jacobi(n) {
+        Some(1) => T::one(),
+        Some(-1) => n - T::one(),
+        _ => return false,
+    };
+    base.pow_mod((n >> 1).to_u128().unwrap(), n) == expected
+}
+pub trait FermatTest: Sized {
+    fn fermat_test(self, base: Self) -> bool;
+    fn fermat_test_iter(self, iterations: usize) -> bool;
+}
+pub trait SolovayStrassenTest: Sized {
+    fn solovay_strassen_test(self, base: Self) -> bool;
+    fn solovay_strassen_test_iter(self, iterations: usize) -> bool;
+}
+// Classifies the bases in `bases`, reduced modulo self. Passing 1..self covers every base,
+// which is only practical for small self.
+pub trait MillerRabinWitnesses: Sized {
+    fn miller_rabin_witnesses(self, bases: std::ops::Range<Self>) -> Vec<Self>;
+    fn liars(self, bases: std::ops::Range<Self>) -> Vec<Self>;
+}
+macro_rules! probable_prime_tests {
+    ($type:ty,$word:ty) => {
+        impl FermatTest for $type {
+            fn fermat_test(self, base: Self) -> bool {
+                self >= 2 && fermat(self as $word, base.rem_euclid(self) as $word)
+            }
+            // Bases divisible by self say nothing about it and are skipped.
+            fn fermat_test_iter(self, iterations: usize) -> bool {
+                self >= 2
+                    && SMALL_PRIMES
+                        .iter()
+                        .take(iterations)
+                        .map(|base| *base as $word % self as $word)
+                        .filter(|base| *base != 0)
+                        .all(|base| fermat(self as $word, base))
+            }
+        }
+        impl SolovayStrassenTest for $type {
+            fn solovay_strassen_test(self, base: Self) -> bool {
+                self >= 2 && solovay_strassen(self as $word, base.rem_euclid(self) as $word)
+            }
+            fn solovay_strassen_test_iter(self, iterations: usize) -> bool {
+                self >= 2
+                    && SMALL_PRIMES
+                        .iter()
+                        .take(iterations)
+                        .map(|base| *base as $word % self as $word)
+                        .filter(|base| *base != 0)
+                        .all(|base| solovay_strassen(self as $word, base))
+            }
+        }
+        impl MillerRabinWitnesses for $type {
+            fn miller_rabin_witnesses(self, bases: std::ops::Range<Self>) -> Vec<Self> {
+                if self < 2 {
+                    return Vec::new();
+                }
+                let exponent = self - 1;
+                let squares = exponent.trailing_zeros();
+                let exponent = exponent >> squares;
+                bases
+                    .filter(|&base| {
+                        miller_rabin_is_witness!(self, base.rem_euclid(self), exponent, squares)
+                    })
+                    .collect()
+            }
+            fn liars(self, bases: std::ops::Range<Self>) -> Vec<Self> {
+                if self < 2 {
+                    return Vec::new();
+                }
+                let exponent = self - 1;
+                let squares = exponent.trailing_zeros();
+                let exponent = exponent >> squares;
+                bases
+                    .filter(|&base| {
+                        !miller_rabin_is_witness!(self, base.rem_euclid(self), exponent, squares)
+                    })
+                    .collect()
+            }
+        }
+    };
+    ($($type:ty,$word:ty);+) => {
+        $(probable_prime_tests!($type,$word);)+
+    };
+}
+probable_prime_tests!(u8,u64;i8,u64;u16,u64;i16,u64;u32,u64;i32,u64;u64,u64;i64,u64;usize,u64;isize,u64;u128,u128;i128,u128);
+fn random_miller_rabin<T, R>(prime: T, rng: &mut R, rounds: usize) -> bool
+where
+    T: MontgomeryWord,
+    R: Rng,
+{
+    let three = T::from(3).unwrap();
+    if prime <= three {
+        return prime > T::one();
+    }
+    if prime & T::one() == T::zero() {
+        return false;
+    }
+    let montgomery = Montgomery::new(prime).unwrap();
+    let exponent = prime >> 1;
+    let squares = exponent.trailing_zeros();
+    let exponent = (exponent >> squares as usize).to_u128().unwrap();
+    let range = (prime - three).to_u128().unwrap();
+    for _ in 0..rounds {
+        let base = T::from(rng.below_u128(range) + 2).unwrap();
+        if montgomery.is_witness(base, exponent, squares) {
+            return false;
+        }
+    }
+    true
+}
+pub trait RandomizedMillerRabinTest: Sized {
+    fn miller_rabin_test_random<R>(self, rng: &mut R, rounds: usize) -> bool
+    where
+        R: Rng;
+    fn miller_rabin_test_target_error<R>(self, rng: &mut R, target_error_bits: u32) -> bool
+    where
+        R: Rng,
+    {
+        // Every round lets a composite through with probability at most 1/4.
+        self.miller_rabin_test_random(rng, target_error_bits.div_ceil(2) as usize)
+    }
+}
+macro_rules! randomized_miller_rabin_test {
+    ($type:ty,$word:ty) => {
+        impl RandomizedMillerRabinTest for $type {
+            fn miller_rabin_test_random<R>(self, rng: &mut R, rounds: usize) -> bool
+            where
+                R: Rng,
+            {
+                if self < 2 {
+                    return false;
+                }
+                random_miller_rabin(self as $word, rng, rounds)
+            }
+        }
+    };
+    ($($type:ty,$word:ty);+) => {
+        $(randomized_miller_rabin_test!($type,$word);)+
+    };
+}
+randomized_miller_rabin_test!(u8,u64;i8,u64;u16,u64;i16,u64;u32,u64;i32,u64;u64,u64;i64,u64;usize,u64;isize,u64;u128,u128;i128,u128);
+impl RandomizedMillerRabinTest for num_bigint::BigUint {
+    fn miller_rabin_test_random<R>(self, rng: &mut R, rounds: usize) -> bool
+    where
+        R: Rng,
+    {
+        use num::ToPrimitive;
+        use num_bigint::BigUint;
+        if let Some(prime) = self.to_u128() {
+            return prime.miller_rabin_test_random(rng, rounds);
+        }
+        let prime = self;
+        if !prime.bit(0) {
+            return false;
+        }
+        let exponent: BigUint = &prime >> 1;
+        let squares = exponent.trailing_zeros().unwrap();
+        let exponent = exponent >> squares;
+        for _ in 0..rounds {
+            if big_is_witness(&prime, &big_random_witness(&prime, rng), &exponent, squares) {
+                return false;
+            }
+        }
+        true
+    }
+}
+impl RandomizedMillerRabinTest for num_bigint::BigInt {
+    fn miller_rabin_test_random<R>(self, rng: &mut R, rounds: usize) -> bool
+    where
+        R: Rng,
+    {
+        match self.to_biguint() {
+            Some(prime) => prime.miller_rabin_test_random(rng, rounds),
+            None => false,
+        }
+    }
+}
+impl<T> RandomizedMillerRabinTest for &T
+where
+    T: RandomizedMillerRabinTest + Clone,
+{
+    fn miller_rabin_test_random<R>(self, rng: &mut R, rounds: usize) -> bool
+    where
+        R: Rng,
+    {
+        <T as RandomizedMillerRabinTest>::miller_rabin_test_random(self.clone(), rng, rounds)
+    }
+}
+impl<T> RandomizedMillerRabinTest for std::rc::Rc<T>
+where
+    T: RandomizedMillerRabinTest + Clone,
+{
+    fn miller_rabin_test_random<R>(self, rng: &mut R, rounds: usize) -> bool
+    where
+        R: Rng,
+    {
+        <T as RandomizedMillerRabinTest>::miller_rabin_test_random((*self).clone(), rng, rounds)
+    }
+}
+impl<T> RandomizedMillerRabinTest for std::sync::Arc<T>
+where
+    T: RandomizedMillerRabinTest + Clone,
+{
+    fn miller_rabin_test_random<R>(self, rng: &mut R, rounds: usize) -> bool
+    where
+        R: Rng,
+    {
+        <T as RandomizedMillerRabinTest>::miller_rabin_test_random((*self).clone(), rng, rounds)
+    }
+}
+const SMALL_PRIMES: [u64; 15] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
+fn is_square<T>(n: T) -> bool
+where
+    T: num::PrimInt,
+{
+    if n < T::from(2).unwrap() {
+        return true;
+    }
+    let bits = T::zero().count_zeros() - n.leading_zeros();
+    let mut root = T::one() << bits.div_ceil(2) as usize;
+    loop {
+        let next = (root + n / root) >> 1;
+        if next >= root {
+            break;
+        }
+        root = next;
+    }
+    root * root == n
+}
+pub(crate) fn big_is_square(n: &num_bigint::BigUint) -> bool {
+    n.sqrt().pow(2) == *n
+}
+fn strong_lucas_selfridge<T>(n: T, montgomery: &Montgomery<T>) -> bool
+where
+    T: MontgomeryWord + JacobiSymbol,
+{
+    let q = match selfridge_parameter(n) {
+        Ok(q) => montgomery.to_montgomery(q),
+        Err(result) => return result,
+    };
+    let successor = n + T::one();
+    let squares = successor.trailing_zeros();
+    let exponent = successor >> squares as usize;
+    let (u, mut v, mut q_k) = montgomery_lucas(montgomery.one(), q, exponent, montgomery);
+    if u == T::zero() {
+        return true;
+    }
+    for _ in 0..squares {
+        if v == T::zero() {
+            return true;
+        }
+        v = montgomery.sub(montgomery.square(v), montgomery.add(q_k, q_k));
+        q_k = montgomery.square(q_k);
+    }
+    false
+}
+fn baillie_psw<T>(n: T) -> bool
+where
+    T: MontgomeryWord + JacobiSymbol,
+{
+    for prime in SMALL_PRIMES {
+        let prime = T::from(prime).unwrap();
+        if n == prime {
+            return true;
+        }
+        if n % prime == T::zero() {
+            return false;
+        }
+    }
+    if n < T::from(2).unwrap() {
+        return false;
+    }
+    let montgomery = Montgomery::new(n).unwrap();
+    let exponent = n >> 1;
+    let squares = exponent.trailing_zeros();
+    let exponent = exponent >> squares as usize;
+    if montgomery.is_witness(T::from(2).unwrap(), exponent.to_u128().unwrap(), squares) {
+        return false;
+    }
+    if is_square(n) {
+        return false;
+    }
+    strong_lucas_selfridge(n, &montgomery)
+}
+fn big_strong_lucas_selfridge(n: &num_bigint::BigUint) -> bool {
+    use num::{One, ToPrimitive, Zero};
+    use num_bigint::BigUint;
+    let half = |x: BigUint| {
+        if x.bit(0) { (x + n) >> 1 } else { x >> 1 }
+    };
+    let add = |a: &BigUint, b: &BigUint| (a + b) % n;
+    let sub = |a: &BigUint, b: &BigUint| (a + n - b) % n;
+    let mul = |a: &BigUint, b: &BigUint| a * b % n;
+    let mut d_abs = 5u64;
+    let mut d_negative = false;
+    loop {
+        let d_mod_n = (d_abs % n).to_u64().unwrap();
+        let d_mod_n = if d_negative && d_mod_n != 0 {
+            n - d_mod_n
+        } else {
+            BigUint::from(d_mod_n)
+        };
+        match d_mod_n.jacobi(n.clone()) {
+            Some(-1) => break,
+            Some(0) => return *n == BigUint::from(d_abs),
+            _ => {}
+        }
+        d_abs += 2;
+        d_negative = !d_negative;
+    }
+    let d = if d_negative {
+        n - d_abs
+    } else {
+        BigUint::from(d_abs)
+    };
+    let q = if d_negative {
+        BigUint::from((d_abs + 1) / 4)
+    } else {
+        n - (d_abs + 1) / 4
+    };
+    let successor = n + 1u32;
+    let squares = successor.trailing_zeros().unwrap();
+    let exponent = successor >> squares;
+    let (mut u, mut v, mut q_k) = (BigUint::one(), BigUint::one(), q.clone());
+    for bit in (0..exponent.bits() - 1).rev() {
+        u = mul(&u, &v);
+        v = sub(&mul(&v, &v), &add(&q_k, &q_k));
+        q_k = mul(&q_k, &q_k);
+        if exponent.bit(bit) {
+            let next_u = half(add(&u, &v));
+            v = half(add(&mul(&d, &u), &v));
+            u = next_u;
+            q_k = mul(&q_k, &q);
+        }
+    }
+    if u.is_zero() {
+        return true;
+    }
+    for _ in 0..squares {
+        if v.is_zero() {
+            return true;
+        }
+        v = sub(&mul(&v, &v), &add(&q_k, &q_k));
+        q_k = mul(&q_k, &q_k);
+    }
+    false
+}
+pub trait BailliePswTest {
+    fn baillie_psw_test(self) -> bool;
+}
+macro_rules! baillie_psw_test {
+    ($type:ty,$word:ty) => {
+        impl BailliePswTest for $type {
+            fn baillie_psw_test(self) -> bool {
+                if self < 2 {
+                    return false;
+                }
+                baillie_psw(self as $word)
+            }
+        }
+    };
+    ($($type:ty,$word:ty);+) => {
+        $(baillie_psw_test!($type,$word);)+
+    };
+}
+baillie_psw_test!(u8,u64;i8,u64;u16,u64;i16,u64;u32,u64;i32,u64;u64,u64;i64,u64;usize,u64;isize,u64);
+impl BailliePswTest for u128 {
+    fn baillie_psw_test(self) -> bool {
+        if self <= u64::MAX as u128 {
+            return (self as u64).baillie_psw_test();
+        }
+        baillie_psw(self)
+    }
+}
+impl BailliePswTest for i128 {
+    fn baillie_psw_test(self) -> bool {
+        if self < 2 {
+            return false;
+        }
+        (self as u128).baillie_psw_test()
+    }
+}
+impl BailliePswTest for num_bigint::BigUint {
+    fn baillie_psw_test(self) -> bool {
+        use num::{ToPrimitive, Zero};
+        use num_bigint::BigUint;
+        if let Some(small) = self.to_u128() {
+            return small.baillie_psw_test();
+        }
+        let n = self;
+        if SMALL_PRIMES.iter().any(|prime| (&n % prime).is_zero()) {
+            return false;
+        }
+        let exponent: BigUint = &n >> 1;
+        let squares = exponent.trailing_zeros().unwrap();
+        let exponent = exponent >> squares;
+        if big_is_witness(&n, &BigUint::from(2u32), &exponent, squares) {
+            return false;
+        }
+        if big_is_square(&n) {
+            return false;
+        }
+        big_strong_lucas_selfridge(&n)
+    }
+}
+impl<T> BailliePswTest for &T
+where
+    T: BailliePswTest + Clone,
+{
+    fn baillie_psw_test(self) -> bool {
+        <T as BailliePswTest>::baillie_psw_test(self.clone())
+    }
+}
+impl<T> BailliePswTest for std::rc::Rc<T>
+where
+    T: BailliePswTest + Clone,
+{
+    fn baillie_psw_test(self) -> bool {
+        <T as BailliePswTest>::baillie_psw_test((*self).clone())
+    }
+}
+impl<T> BailliePswTest for std::sync::Arc<T>
+where
+    T: BailliePswTest + Clone,
+{
+    fn baillie_psw_test(self) -> bool {
+        <T as BailliePswTest>::baillie_psw_test((*self).clone())
+    }
+}
+// The doubling ladder for U_k, V_k and Q^k, with p, q and the results in Montgomery form.
+fn montgomery_lucas<T>(p: T, q: T, k: T, montgomery: &Montgomery<T>) -> (T, T, T)
+where
+    T: MontgomeryWord,
+{
+    let modulus = montgomery.modulus();
+    let half = |x: T| {
+        if x & T::one() == T::zero() {
+            x >> 1
+        } else {
+            (x >> 1) + (modulus >> 1) + T::one()
+        }
+    };
+    let one = montgomery.one();
+    if k == T::zero() {
+        return (T::zero(), montgomery.add(one, one), one);
+    }
+    let double_q = montgomery.add(q, q);
+    let d = montgomery.sub(montgomery.square(p), montgomery.add(double_q, double_q));
+    let (mut u, mut v, mut q_k) = (one, p, q);
+    let bits = T::zero().count_zeros() - k.leading_zeros();
+    for bit in (0..bits - 1).rev() {
+        u = montgomery.mul(u, v);
+        v = montgomery.sub(montgomery.square(v), montgomery.add(q_k, q_k));
+        q_k = montgomery.square(q_k);
+        if (k >> bit as usize) & T::one() == T::one() {
+            let next_u = half(montgomery.add(montgomery.mul(p, u), v));
+            v = half(montgomery.add(montgomery.mul(d, u), montgomery.mul(p, v)));
+            u = next_u;
+            q_k = montgomery.mul(q_k, q);
+        }
+    }
+    (u, v, q_k)
+}
+// Returns (U_k, V_k, Q^k) modulo an odd modulus, or None when the modulus is even.
+pub fn lucas_sequence<T>(p: T, q: T, k: T, modulus: T) -> Option<(T, T, T)>
+where
+    T: MontgomeryWord,
+{
+    let montgomery = Montgomery::new(modulus)?;
+    let (p, q) = (montgomery.to_montgomery(p), montgomery.to_montgomery(q));
+    let (u, v, q_k) = montgomery_lucas(p, q, k, &montgomery);
+    Some((
+        montgomery.from_montgomery(u),
+        montgomery.from_montgomery(v),
+        montgomery.from_montgomery(q_k),
+    ))
+}
+fn lucas_trial_division<T>(n: T) -> Option<bool>
+where
+    T: num::PrimInt,
+{
+    if n < T::from(2).unwrap() {
+        return Some(false);
+    }
+    for prime in SMALL_PRIMES {
+        let prime = T::from(prime).unwrap();
+        if n == prime {
+            return Some(true);
+        }
+        if n % prime == T::zero() {
+            return Some(false);
+        }
+    }
+    if is_square(n) {
+        return Some(false);
+    }
+    None
+}
+fn lucas_discriminant<T>(n: T, discriminant: T) -> Option<bool>
+where
+    T: num::PrimInt + JacobiSymbol,
+{
+    match discriminant.jacobi(n) {
+        Some(-1) => Some(true),
+        Some(0) if discriminant != T::zero() => Some(false),
+        _ => None,
+    }
+}
+fn selfridge_parameter<T>(n: T) -> Result<T, bool>
+where
+    T: num::PrimInt + JacobiSymbol,
+{
+    let mut d_abs = 5u64;
+    let mut d_negative = false;
+    loop {
+        let d_mod_n = T::from(d_abs).unwrap() % n;
+        let d_mod_n = if d_negative && d_mod_n != T::zero() {
+            n - d_mod_n
+        } else {
+            d_mod_n
+        };
+        match lucas_discriminant(n, d_mod_n) {
+            Some(true) => break,
+            Some(false) => return Err(false),
+            None => {}
+        }
+        d_abs += 2;
+        d_negative = !d_negative;
+    }
+    // Q = (1 - D) / 4, which is negative exactly when D is positive.
+    let q_abs = T::from((d_abs + 1) / 4).unwrap() % n;
+    if d_negative || q_abs == T::zero() {
+        Ok(q_abs)
+    } else {
+        Ok(n - q_abs)
+    }
+}
+fn strong_lucas<T>(n: T) -> bool
+where
+    T: MontgomeryWord + JacobiSymbol,
+{
+    if let Some(result) = lucas_trial_division(n) {
+        return result;
+    }
+    strong_lucas_selfridge(n, &Montgomery::new(n).unwrap())
+}
+fn extra_strong_lucas<T>(n: T) -> bool
+where
+    T: MontgomeryWord + JacobiSymbol,
+{
+    if let Some(result) = lucas_trial_division(n) {
+        return result;
+    }
+    let mut p = 3u64;
+    loop {
+        match lucas_discriminant(n, T::from(p * p - 4).unwrap() % n) {
+            Some(true) => break,
+            Some(false) => return false,
+            None => p += 1,
+        }
+    }
+    let montgomery = Montgomery::new(n).unwrap();
+    let two = montgomery.add(montgomery.one(), montgomery.one());
+    let successor = n + T::one();
+    let squares = successor.trailing_zeros();
+    let exponent = successor >> squares as usize;
+    let p = montgomery.to_montgomery(T::from(p).unwrap());
+    let (u, mut v, _) = montgomery_lucas(p, montgomery.one(), exponent, &montgomery);
+    if u == T::zero() && (v == two || v == montgomery.neg(two)) {
+        return true;
+    }
+    for _ in 1..squares {
+        if v == T::zero() {
+            return true;
+        }
+        v = montgomery.sub(montgomery.square(v), two);
+    }
+    false
+}
+fn frobenius<T>(n: T) -> bool
+where
+    T: MontgomeryWord + num::Integer + JacobiSymbol,
+{
+    if let Some(result) = lucas_trial_division(n) {
+        return result;
+    }
+    let q = match selfridge_parameter(n) {
+        Ok(q) => q,
+        Err(result) => return result,
+    };
+    if q.gcd(&n) != T::one() {
+        return false;
+    }
+    let montgomery = Montgomery::new(n).unwrap();
+    let q_montgomery = montgomery.to_montgomery(q);
+    let (u, v, _) = montgomery_lucas(montgomery.one(), q_montgomery, n + T::one(), &montgomery);
+    // Euler's criterion on Q: Q^((n - 1) / 2) must match the Jacobi symbol (Q/n).
+    let euler = montgomery.pow(q_montgomery, (n >> 1).to_u128().unwrap());
+    let symbol = if q.jacobi(n) == Some(1) {
+        montgomery.one()
+    } else {
+        montgomery.minus_one()
+    };
+    u == T::zero() && v == montgomery.add(q_montgomery, q_montgomery) && euler == symbol
+}
+pub trait StrongLucasTest {
+    fn strong_lucas_test(self) -> bool;
+}
+pub trait ExtraStrongLucasTest {
+    fn extra_strong_lucas_test(self) -> bool;
+}
+pub trait FrobeniusTest {
+    fn frobenius_test(self) -> bool;
+}
+macro_rules! lucas_tests {
+    ($type:ty,$word:ty) => {
+        impl StrongLucasTest for $type {
+            fn strong_lucas_test(self) -> bool {
+                self >= 2 && strong_lucas(self as $word)
+            }
+        }
+        impl ExtraStrongLucasTest for $type {
+            fn extra_strong_lucas_test(self) -> bool {
+                self >= 2 && extra_strong_lucas(self as $word)
+            }
+        }
+        impl FrobeniusTest for $type {
+            fn frobenius_test(self) -> bool {
+                self >= 2 && frobenius(self as $word)
+            }
+        }
+    };
+    ($($type:ty,$word:ty);+) => {
+        $(lucas_tests!($type,$word);)+
+    };
+}
+lucas_tests!(u8,u64;i8,u64;u16,u64;i16,u64;u32,u64;i32,u64;u64,u64;i64,u64;usize,u64;isize,u64;u128,u128;i128,u128);
+macro_rules! lucas_test_references {
+    ($trait:ident,$method:ident) => {
+        impl<T> $trait for &T
+        where
+            T: $trait + Clone,
+        {
+            fn $method(self) -> bool {
+                <T as $trait>::$method(self.clone())
+            }
+        }
+        impl<T> $trait for std::rc::Rc<T>
+        where
+            T: $trait + Clone,
+        {
+            fn $method(self) -> bool {
+                <T as $trait>::$method((*self).clone())
+            }
+        }
+        impl<T> $trait for std::sync::Arc<T>
+        where
+            T: $trait + Clone,
+        {
+            fn $method(self) -> bool {
+                <T as $trait>::$method((*self).clone())
+            }
+        }
+    };
+}
+lucas_test_references!(StrongLucasTest, strong_lucas_test);
+lucas_test_references!(ExtraStrongLucasTest, extra_strong_lucas_test);
+lucas_test_references!(FrobeniusTest, frobenius_test);
+fn passes_trial_division(candidate: u128) -> bool {
+    SMALL_PRIMES
+        .iter()
+        .all(|prime| candidate == *prime as u128 || !candidate.is_multiple_of(*prime as u128))
+}
+pub trait PrimeNavigation: Sized {
+    fn next_prime(self) -> Option<Self>;
+    fn prev_prime(self) -> Option<Self>;
+    fn nth_prime(n: usize) -> Option<Self>;
+}
+macro_rules! prime_navigation {
+    ($type:ty) => {
+        impl PrimeNavigation for $type {
+            fn next_prime(self) -> Option<Self> {
+                if self <= 2 {
+                    return Some(2);
+                }
+                let mut candidate = self;
+                while candidate % 6 != 1 && candidate % 6 != 5 {
+                    if candidate == 3 {
+                        return Some(3);
+                    }
+                    candidate = candidate.checked_add(1)?;
+                }
+                loop {
+                    if passes_trial_division(candidate as u128) && candidate.miller_rabin_test() {
+                        return Some(candidate);
+                    }
+                    candidate = candidate.checked_add(if candidate % 6 == 1 { 4 } else { 2 })?;
+                }
+            }
+            fn prev_prime(self) -> Option<Self> {
+                if self < 2 {
+                    return None;
+                }
+                if self < 5 {
+                    return Some(if self < 3 { 2 } else { 3 });
+                }
+                let mut candidate = self;
+                while candidate % 6 != 1 && candidate % 6 != 5 {
+                    candidate -= 1;
+                }
+                loop {
+                    if passes_trial_division(candidate as u128) && candidate.miller_rabin_test() {
+                        return Some(candidate);
+                    }
+                    candidate -= if candidate % 6 == 5 { 4 } else { 2 };
+                }
+            }
+            fn nth_prime(n: usize) -> Option<Self> {
+                let limit = u64::try_from(Self::MAX).unwrap_or(u64::MAX);
+                let prime = super::sieve::Primes::new()
+                    .take_while(|prime| *prime <= limit)
+                    .nth(n.checked_sub(1)?)?;
+                Self::try_from(prime).ok()
+            }
+        }
+    };
+    ($($type:ty),+) => {
+        $(prime_navigation!($type);)+
+    };
+}
+prime_navigation!(
+    u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, u128, i128
+);
+impl PrimeNavigation for num_bigint::BigUint {
+    fn next_prime(self) -> Option<Self> {
+        use num::ToPrimitive;
+        if let Some(candidate) = self.to_u64()
+            && let Some(prime) = candidate.next_prime()
+        {
+            return Some(prime.into());
+        }
+        let mut candidate = self;
+        while !matches!((&candidate % 6u32).to_u8(), Some(1 | 5)) {
+            candidate += 1u32;
+        }
+        loop {
+            if SMALL_PRIMES
+                .iter()
+                .all(|prime| &candidate % prime != 0u32.into())
+                && candidate.clone().miller_rabin_test()
+            {
+                return Some(candidate);
+            }
+            candidate += if &candidate % 6u32 == 1u32.into() {
+                4u32
+            } else {
+                2u32
+            };
+        }
+    }
+    fn prev_prime(self) -> Option<Self> {
+        use num::ToPrimitive;
+        if let Some(candidate) = self.to_u64() {
+            return candidate.prev_prime().map(Into::into);
+        }
+        let mut candidate = self;
+        while !matches!((&candidate % 6u32).to_u8(), Some(1 | 5)) {
+            candidate -= 1u32;
+        }
+        loop {
+            if SMALL_PRIMES
+                .iter()
+                .all(|prime| &candidate % prime != 0u32.into())
+                && candidate.clone().miller_rabin_test()
+            {
+                return Some(candidate);
+            }
+            candidate -= if &candidate % 6u32 == 5u32.into() {
+                4u32
+            } else {
+                2u32
+            };
+        }
+    }
+    fn nth_prime(n: usize) -> Option<Self> {
+        u64::nth_prime(n).map(Into::into)
+    }
+}
@@ -0,0 +1,242 @@
+use super::modular::Modular;
+use super::primality::MillerRabinTest;
+use super::sieve::primes_up_to;
+use milans_rust_core::random::Rng;
+use num::{CheckedAdd, CheckedMul, FromPrimitive, Integer, ToPrimitive};
+use num_bigint::BigUint;
+const SIEVE_LIMIT: u64 = 1 << 16;
+const STRONG_PRIME_MIN_BITS: u32 = 32;
+fn power_of_two<T>(bits: u32) -> T
+where
+    T: Integer + FromPrimitive,
+{
+    let mut value = T::one();
+    for _ in 0..bits / 32 {
+        value = value * T::from_u64(1 << 32).unwrap();
+    }
+    value * T::from_u64(1 << (bits % 32)).unwrap()
+}
+fn maximum<T>(bits: u32) -> T
+where
+    T: Clone + Integer + FromPrimitive,
+{
+    let half = power_of_two::<T>(bits - 1);
+    half.clone() - T::one() + half
+}
+fn random_candidate<T, R>(bits: u32, rng: &mut R) -> T
+where
+    T: Integer + FromPrimitive,
+    R: Rng,
+{
+    let mut value = T::zero();
+    let mut remaining = bits - 1;
+    while remaining > 0 {
+        let chunk = remaining.min(32);
+        value = value * power_of_two(chunk) + T::from_u64(rng.next_u64() >> (64 - chunk)).unwrap();
+        remaining -= chunk;
+    }
+    power_of_two::<T>(bits - 1) + value
+}
+fn align<T>(value: T, residue: &T, modulus: &T, top: &T) -> Option<T>
+where
+    T: Clone + Integer,
+{
+    let offset =
+        (residue.clone() + modulus.clone() - value.clone() % modulus.clone()) % modulus.clone();
+    if value > *top || offset > top.clone() - value.clone() {
+        return None;
+    }
+    Some(value + offset)
+}
+fn sieve_primes(bits: u32) -> Vec<u64> {
+    let limit = (bits as u64 * bits as u64).min(SIEVE_LIMIT);
+    primes_up_to(limit).into_iter().skip(1).collect()
+}
+fn sieve_search<T>(mut start: T, step: &T, top: &T, safe: bool, primes: &[u64]) -> Option<T>
+where
+    T: Clone + Integer + CheckedAdd + CheckedMul + FromPrimitive + ToPrimitive + MillerRabinTest,
+{
+    let two = T::one() + T::one();
+    let smallest = if safe {
+        (start.clone() - T::one()) / two.clone()
+    } else {
+        start.clone()
+    };
+    let bound = smallest.to_u64().unwrap_or(u64::MAX);
+    let sieve: Vec<(u64, u64)> = primes
+        .iter()
+        .take_while(|&&prime| prime < bound)
+        .filter_map(|&prime| {
+            let residue = (step.clone() % T::from_u64(prime).unwrap())
+                .to_u64()
+                .unwrap();
+            residue.mod_inverse(prime).map(|inverse| (prime, inverse))
+        })
+        .collect();
+    let window = 4 * primes.len().max(64);
+    while start <= *top {
+        let remaining = (top.clone() - start.clone()) / step.clone() + T::one();
+        let count = remaining
+            .to_usize()
+            .map_or(window, |remaining| remaining.min(window));
+        let mut composite = vec![false; count];
+        for &(prime, inverse) in &sieve {
+            let residue = (start.clone() % T::from_u64(prime).unwrap())
+                .to_u64()
+                .unwrap();
+            let forbidden: &[u64] = if safe { &[0, 1] } else { &[0] };
+            for &target in forbidden {
+                let first = (target + prime - residue) % prime * inverse % prime;
+                for index in (first as usize..count).step_by(prime as usize) {
+                    composite[index] = true;
+                }
+            }
+        }
+        for (index, _) in composite
+            .iter()
+            .enumerate()
+            .filter(|(_, composite)| !**composite)
+        {
+            let candidate = start.clone() + step.clone() * T::from_usize(index).unwrap();
+            if candidate.clone().miller_rabin_test()
+                && (!safe || ((candidate.clone() - T::one()) / two.clone()).miller_rabin_test())
+            {
+                return Some(candidate);
+            }
+        }
+        if count < window {
+            break;
+        }
+        // With top == T::MAX the next window can start past the end of the type.
+        let Some(next) = step
+            .checked_mul(&T::from_usize(count).unwrap())
+            .and_then(|advance| start.checked_add(&advance))
+        else {
+            break;
+        };
+        start = next;
+    }
+    None
+}
+fn prime_with_bits<T, R>(bits: u32, rng: &mut R) -> T
+where
+    T: Clone + Integer + CheckedAdd + CheckedMul + FromPrimitive + ToPrimitive + MillerRabinTest,
+    R: Rng,
+{
+    let (primes, top, two) = (sieve_primes(bits), maximum::<T>(bits), T::one() + T::one());
+    loop {
+        let candidate = random_candidate::<T, R>(bits, rng);
+        let start = if candidate.is_even() {
+            candidate + T::one()
+        } else {
+            candidate
+        };
+        if let Some(prime) = sieve_search(start, &two, &top, false, &primes) {
+            return prime;
+        }
+    }
+}
+fn safe_prime_with_bits<T, R>(bits: u32, rng: &mut R) -> T
+where
+    T: Clone + Integer + CheckedAdd + CheckedMul + FromPrimitive + ToPrimitive + MillerRabinTest,
+    R: Rng,
+{
+    let (primes, top, four) = (
+        sieve_primes(bits),
+        maximum::<T>(bits),
+        T::from_u64(4).unwrap(),
+    );
+    loop {
+        let candidate = random_candidate::<T, R>(bits, rng);
+        let start = candidate.clone() - candidate % four.clone() + T::from_u64(3).unwrap();
+        if let Some(prime) = sieve_search(start, &four, &top, true, &primes) {
+            return prime;
+        }
+    }
+}
+fn strong_prime_with_bits<T, R>(bits: u32, rng: &mut R) -> T
+where
+    T: Clone
+        + Integer
+        + CheckedAdd
+        + CheckedMul
+        + FromPrimitive
+        + ToPrimitive
+        + MillerRabinTest
+        + Modular,
+    R: Rng,
+{
+    let factor_bits = bits / 2 - 8;
+    let (factor_primes, factor_top) = (sieve_primes(factor_bits), maximum::<T>(factor_bits));
+    let (primes, top, two) = (sieve_primes(bits), maximum::<T>(bits), T::one() + T::one());
+    loop {
+        let s: T = prime_with_bits(factor_bits, rng);
+        let t: T = prime_with_bits(factor_bits / 2, rng);
+        let step = two.clone() * t;
+        let r = align(
+            random_candidate(factor_bits, rng),
+            &T::one(),
+            &step,
+            &factor_top,
+        )
+        .and_then(|start| sieve_search(start, &step, &factor_top, false, &factor_primes));
+        let Some((r, inverse)) = r.and_then(|r| Some((r.clone(), s.clone().mod_inverse(r)?)))
+        else {
+            continue;
+        };
+        let step = two.clone() * r * s.clone();
+        let residue = two.clone() * inverse * s - T::one();
+        for _ in 0..bits {
+            let prime = align(random_candidate(bits, rng), &residue, &step, &top)
+                .and_then(|start| sieve_search(start, &step, &top, false, &primes));
+            if let Some(prime) = prime {
+                return prime;
+            }
+        }
+    }
+}
+pub trait RandomPrime: Sized {
+    fn random_prime<R>(bits: u32, rng: &mut R) -> Self
+    where
+        R: Rng;
+    fn random_safe_prime<R>(bits: u32, rng: &mut R) -> Self
+    where
+        R: Rng;
+    fn random_strong_prime<R>(bits: u32, rng: &mut R) -> Self
+    where
+        R: Rng;
+}
+macro_rules! random_prime {
+    ($type:ty,$max:expr) => {
+        impl RandomPrime for $type {
+            fn random_prime<R>(bits: u32, rng: &mut R) -> Self
+            where
+                R: Rng,
+            {
+                assert!((2..=$max).contains(&bits), "bit length out of range");
+                prime_with_bits(bits, rng)
+            }
+            fn random_safe_prime<R>(bits: u32, rng: &mut R) -> Self
+            where
+                R: Rng,
+            {
+                assert!((3..=$max).contains(&bits), "bit length out of range");
+                safe_prime_with_bits(bits, rng)
+            }
+            fn random_strong_prime<R>(bits: u32, rng: &mut R) -> Self
+            where
+                R: Rng,
+            {
+                assert!(
+                    (STRONG_PRIME_MIN_BITS..=$max).contains(&bits),
+                    "bit length out of range"
+                );
+                strong_prime_with_bits(bits, rng)
+            }
+        }
+    };
+    ($($type:ty,$max:expr);+) => {
+        $(random_prime!($type,$max);)+
+    };
+}
+random_prime!(u64, u64::BITS; u128, u128::BITS; BigUint, u32::MAX);
@@ -0,0 +1,126 @@
+use super::arithmetic::ArithmeticFunctions;
+use super::factor::Factorize;
+use super::primality::PowMod;
+use num::{Integer, PrimInt};
+fn totient_factors<T>(n: T) -> (T, Vec<T>)
+where
+    T: PrimInt + Integer + Factorize,
+{
+    let totient = n.factorize().euler_totient();
+    (totient, totient.factorize().primes().copied().collect())
+}
+fn has_primitive_root<T>(n: T) -> bool
+where
+    T: PrimInt + Integer + Factorize,
+{
+    let (two, four) = (T::one() + T::one(), T::from(4).unwrap());
+    if n <= four {
+        return true;
+    }
+    let odd = if n.is_even() { n / two } else { n };
+    odd.is_odd() && odd.factorize().len() == 1
+}
+pub fn multiplicative_order<T>(a: T, n: T) -> Option<T>
+where
+    T: PrimInt + Integer + PowMod + Factorize,
+{
+    assert!(n > T::zero(), "modulus must be positive");
+    let a = a.mod_floor(&n);
+    if n == T::one() {
+        return Some(T::one());
+    }
+    if a.gcd(&n) != T::one() {
+        return None;
+    }
+    let mut order = n.factorize().carmichael_lambda();
+    for (prime, exponent) in order.factorize() {
+        for _ in 0..exponent {
+            let candidate = order / prime;
+            if a.pow_mod(candidate.to_u128().unwrap(), n) != T::one() {
+                break;
+            }
+            order = candidate;
+        }
+    }
+    Some(order)
+}
+fn primitive_root_test<T>(g: T, n: T, totient: T, primes: &[T]) -> bool
+where
+    T: PrimInt + Integer + PowMod,
+{
+    let one = T::one() % n;
+    g.gcd(&n) == T::one()
+        && primes
+            .iter()
+            .all(|prime| g.pow_mod((totient / *prime).to_u128().unwrap(), n) % n != one)
+}
+pub fn is_primitive_root<T>(g: T, n: T) -> bool
+where
+    T: PrimInt + Integer + PowMod + Factorize,
+{
+    assert!(n > T::zero(), "modulus must be positive");
+    if !has_primitive_root(n) {
+        return false;
+    }
+    let (totient, primes) = totient_factors(n);
+    primitive_root_test(g.mod_floor(&n), n, totient, &primes)
+}
+// Modulo 1 the unit group is trivial and every residue is 0, so 0 is reported as its root,
+// matching multiplicative_order(a, 1) == Some(1).
+pub fn primitive_root<T>(n: T) -> Option<T>
+where
+    T: PrimInt + Integer + PowMod + Factorize,
+{
+    assert!(n > T::zero(), "modulus must be positive");
+    if n == T::one() {
+        return Some(T::zero());
+    }
+    if !has_primitive_root(n) {
+        return None;
+    }
+    let (totient, primes) = totient_factors(n);
+    let mut g = T::one();
+    while !primitive_root_test(g, n, totient, &primes) {
+        g = g + T::one();
+    }
+    Some(g)
+}
+pub struct PrimitiveRoots<T> {
+    root: T,
+    modulus: T,
+    totient: T,
+    exponent: T,
+    value: T,
+}
+impl<T> Iterator for PrimitiveRoots<T>
+where
+    T: PrimInt + Integer + PowMod,
+{
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        while self.exponent < self.totient {
+            self.exponent = self.exponent + T::one();
+            self.value = self.value.mul_mod(self.root, self.modulus);
+            if self.exponent.gcd(&self.totient) == T::one() {
+                return Some(self.value);
+            }
+        }
+        None
+    }
+}
+pub fn primitive_roots<T>(n: T) -> PrimitiveRoots<T>
+where
+    T: PrimInt + Integer + PowMod + Factorize,
+{
+    let (root, totient) = match primitive_root(n) {
+        Some(root) => (root, totient_factors(n).0),
+        None => (T::zero(), T::zero()),
+    };
+    PrimitiveRoots {
+        root,
+        modulus: n,
+        totient,
+        exponent: T::zero(),
+        value: T::one() % n,
+    }
+}
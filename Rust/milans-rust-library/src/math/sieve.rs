@@ -0,0 +1,216 @@
+const WHEEL: [u64; 8] = [1, 7, 11, 13, 17, 19, 23, 29];
+const WHEEL_INDEX: [u8; 30] = [
+    255, 0, 255, 255, 255, 255, 255, 1, 255, 255, 255, 2, 255, 3, 255, 255, 255, 4, 255, 5, 255,
+    255, 255, 6, 255, 255, 255, 255, 255, 7,
+];
+const SEGMENT_BYTES: u64 = 1 << 15;
+const LARGE_SEGMENT_BYTES: u64 = 1 << 24;
+const SIMPLE_SIEVE_LIMIT: u64 = 1 << 20;
+const STORED_BASE_LIMIT: u64 = 1 << 22;
+const WHEEL_STEPS: [u64; 8] = [6, 4, 2, 4, 2, 4, 6, 2];
+#[derive(Clone, Debug)]
+struct BasePrime {
+    prime: u32,
+    position: u32,
+    multiple: u64,
+}
+impl BasePrime {
+    fn new(prime: u64, low: u64) -> Self {
+        let start = (prime * prime).max(low);
+        let mut factor = start.div_ceil(prime);
+        while WHEEL_INDEX[(factor % 30) as usize] == 255 {
+            factor += 1;
+        }
+        BasePrime {
+            prime: prime as u32,
+            position: WHEEL_INDEX[(factor % 30) as usize] as u32,
+            multiple: prime.saturating_mul(factor),
+        }
+    }
+}
+fn simple_primes(limit: u64) -> Vec<u64> {
+    let mut composite = vec![false; limit as usize + 1];
+    let mut primes = Vec::new();
+    for candidate in 2..=limit {
+        if composite[candidate as usize] {
+            continue;
+        }
+        primes.push(candidate);
+        let mut multiple = candidate * candidate;
+        while multiple <= limit {
+            composite[multiple as usize] = true;
+            multiple += candidate;
+        }
+    }
+    primes
+}
+fn base_primes(limit: u64) -> Vec<u64> {
+    if limit <= SIMPLE_SIEVE_LIMIT {
+        simple_primes(limit)
+    } else {
+        primes_up_to(limit)
+    }
+}
+#[derive(Clone, Debug)]
+pub struct Primes {
+    start: u64,
+    low: u64,
+    segment: Vec<u8>,
+    byte: usize,
+    bit: u32,
+    base: Vec<BasePrime>,
+    base_limit: u64,
+    small: usize,
+    finished: bool,
+}
+impl Primes {
+    pub fn new() -> Self {
+        Primes::starting_at(0)
+    }
+    pub fn starting_at(start: u64) -> Self {
+        let mut primes = Primes {
+            start,
+            low: start / 30 * 30,
+            segment: Vec::new(),
+            byte: 0,
+            bit: 0,
+            base: Vec::new(),
+            base_limit: 5,
+            small: [2, 3, 5].iter().filter(|prime| **prime < start).count(),
+            finished: false,
+        };
+        primes.sieve_segment();
+        primes
+    }
+    fn extend_base(&mut self, high: u64) {
+        let root = (high - 1).isqrt();
+        if root <= self.base_limit || self.base_limit >= STORED_BASE_LIMIT {
+            return;
+        }
+        let limit = root.max(self.base_limit * 2).min(STORED_BASE_LIMIT);
+        for prime in base_primes(limit) {
+            if prime > self.base_limit {
+                self.base.push(BasePrime::new(prime, self.low));
+            }
+        }
+        self.base_limit = limit;
+    }
+    fn sieve_streamed(&mut self, high: u64) {
+        // Base primes above the stored ones are regenerated for every segment instead of kept.
+        let primes = Primes::starting_at(STORED_BASE_LIMIT + 1);
+        for prime in primes.take_while(|prime| {
+            prime
+                .checked_mul(*prime)
+                .is_some_and(|square| square < high)
+        }) {
+            let Some(mut multiple) = self.low.div_ceil(prime).checked_mul(prime) else {
+                continue;
+            };
+            while multiple < high {
+                let offset = multiple - self.low;
+                let index = WHEEL_INDEX[(offset % 30) as usize];
+                if index != 255 {
+                    self.segment[(offset / 30) as usize] |= 1 << index;
+                }
+                let Some(next) = multiple.checked_add(prime) else {
+                    break;
+                };
+                multiple = next;
+            }
+        }
+    }
+    fn sieve_segment(&mut self) {
+        let streamed = self.low.saturating_add(SEGMENT_BYTES * 30).isqrt() > STORED_BASE_LIMIT;
+        let bytes = if streamed {
+            LARGE_SEGMENT_BYTES
+        } else {
+            SEGMENT_BYTES
+        };
+        let bytes = bytes.min((u64::MAX - self.low) / 30 + 1);
+        let high = self.low.saturating_add(bytes * 30);
+        self.extend_base(high);
+        self.segment.clear();
+        self.segment.resize(bytes as usize, 0);
+        if self.low == 0 {
+            self.segment[0] |= 1;
+        }
+        for base in self.base.iter_mut() {
+            let prime = base.prime as u64;
+            if prime * prime >= high {
+                break;
+            }
+            let mut multiple = base.multiple;
+            let mut position = base.position as usize;
+            while multiple < high {
+                let offset = multiple - self.low;
+                self.segment[(offset / 30) as usize] |= 1 << WHEEL_INDEX[(offset % 30) as usize];
+                multiple = multiple.saturating_add(prime * WHEEL_STEPS[position]);
+                position = (position + 1) & 7;
+            }
+            base.multiple = multiple;
+            base.position = position as u32;
+        }
+        if streamed {
+            self.sieve_streamed(high);
+        }
+        self.byte = 0;
+        self.bit = 0;
+    }
+}
+impl Default for Primes {
+    fn default() -> Self {
+        Primes::new()
+    }
+}
+impl Iterator for Primes {
+    type Item = u64;
+    fn next(&mut self) -> Option<u64> {
+        if self.small < 3 {
+            self.small += 1;
+            return Some([2, 3, 5][self.small - 1]);
+        }
+        while !self.finished {
+            while self.byte < self.segment.len() {
+                let candidates = !self.segment[self.byte] & (0xffu8 << self.bit);
+                if candidates == 0 {
+                    self.byte += 1;
+                    self.bit = 0;
+                    continue;
+                }
+                let byte = self.byte as u64;
+                let bit = candidates.trailing_zeros();
+                self.bit = bit + 1;
+                if self.bit == 8 {
+                    self.byte += 1;
+                    self.bit = 0;
+                }
+                let Some(value) = (self.low + 30 * byte).checked_add(WHEEL[bit as usize]) else {
+                    self.finished = true;
+                    return None;
+                };
+                if value >= self.start {
+                    return Some(value);
+                }
+            }
+            match self.low.checked_add(self.segment.len() as u64 * 30) {
+                Some(low) => {
+                    self.low = low;
+                    self.sieve_segment();
+                }
+                None => self.finished = true,
+            }
+        }
+        None
+    }
+}
+pub fn primes_in_range(range: std::ops::Range<u64>) -> Vec<u64> {
+    if range.is_empty() {
+        return Vec::new();
+    }
+    Primes::starting_at(range.start)
+        .take_while(|prime| *prime < range.end)
+        .collect()
+}
+pub fn primes_up_to(n: u64) -> Vec<u64> {
+    primes_in_range(0..n.saturating_add(1))
+}
@@ -0,0 +1,176 @@
+use super::modular::JacobiSymbol;
+use super::primality::{MillerRabinTest, big_is_square};
+use super::sieve::Primes;
+use num::{One, Zero};
+use num_bigint::BigUint;
+// Reduction modulo k * 2^n + 1 or k * 2^n - 1 by shifts and a division by k.
+pub struct SpecialModulus {
+    multiplier: u64,
+    exponent: u32,
+    plus: bool,
+    modulus: BigUint,
+    mask: BigUint,
+}
+impl SpecialModulus {
+    pub fn new(multiplier: u64, exponent: u32, plus: bool) -> Self {
+        assert!(
+            multiplier > 0 && exponent > 0,
+            "multiplier and exponent must be positive"
+        );
+        let power = BigUint::one() << exponent;
+        let product = &power * multiplier;
+        let modulus = if plus { product + 1u32 } else { product - 1u32 };
+        SpecialModulus {
+            multiplier,
+            exponent,
+            plus,
+            modulus,
+            mask: power - 1u32,
+        }
+    }
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+    pub fn reduce(&self, mut x: BigUint) -> BigUint {
+        // Writing x = (a * multiplier + b) * 2^exponent + r gives
+        // x ≡ b * 2^exponent + r ∓ a, since multiplier * 2^exponent ≡ ∓1.
+        while x > self.modulus {
+            let low = &x & &self.mask;
+            let high = x >> self.exponent;
+            let (quotient, remainder) = (&high / self.multiplier, &high % self.multiplier);
+            let rest = (remainder << self.exponent) | low;
+            x = if !self.plus {
+                rest + quotient
+            } else if rest >= quotient {
+                rest - quotient
+            } else {
+                let deficit = self.reduce(quotient - rest);
+                if deficit.is_zero() {
+                    deficit
+                } else {
+                    &self.modulus - deficit
+                }
+            };
+        }
+        if x == self.modulus {
+            BigUint::zero()
+        } else {
+            x
+        }
+    }
+    fn square(&self, x: &BigUint) -> BigUint {
+        self.reduce(x * x)
+    }
+    fn mul(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        self.reduce(x * y)
+    }
+    fn sub(&self, x: BigUint, y: &BigUint) -> BigUint {
+        if x >= *y {
+            x - y
+        } else {
+            x + &self.modulus - y
+        }
+    }
+    fn pow(&self, base: &BigUint, exponent: u64) -> BigUint {
+        let mut result = self.reduce(BigUint::one());
+        for bit in (0..u64::BITS - exponent.leading_zeros()).rev() {
+            result = self.square(&result);
+            if exponent >> bit & 1 == 1 {
+                result = self.mul(&result, base);
+            }
+        }
+        result
+    }
+    fn lucas_v(&self, p: &BigUint, index: u64) -> BigUint {
+        let two = self.reduce(BigUint::from(2u32));
+        let (mut low, mut high) = (two.clone(), p.clone());
+        for bit in (0..u64::BITS - index.leading_zeros()).rev() {
+            let middle = self.sub(self.mul(&low, &high), p);
+            if index >> bit & 1 == 1 {
+                low = middle;
+                high = self.sub(self.square(&high), &two);
+            } else {
+                high = middle;
+                low = self.sub(self.square(&low), &two);
+            }
+        }
+        low
+    }
+}
+pub fn lucas_lehmer(p: u32) -> bool {
+    if p == 2 {
+        return true;
+    }
+    if !p.miller_rabin_test() {
+        return false;
+    }
+    let mersenne = SpecialModulus::new(1, p, false);
+    let (mut s, two) = (BigUint::from(4u32), BigUint::from(2u32));
+    for _ in 0..p - 2 {
+        s = mersenne.sub(mersenne.square(&s), &two);
+    }
+    s.is_zero()
+}
+fn proth_with_base(modulus: &SpecialModulus, base: u64) -> bool {
+    let n = &modulus.modulus;
+    let mut power = modulus.pow(&BigUint::from(base), modulus.multiplier);
+    for _ in 1..modulus.exponent {
+        power = modulus.square(&power);
+    }
+    power == n - 1u32
+}
+pub fn proth_test(k: u64, n: u32) -> bool {
+    assert!(
+        k % 2 == 1 && (n >= u64::BITS || k < 1 << n),
+        "k must be odd and smaller than 2^n"
+    );
+    let proth = SpecialModulus::new(k, n, true);
+    let number = &proth.modulus;
+    if big_is_square(number) {
+        return false;
+    }
+    for base in Primes::new() {
+        match BigUint::from(base).jacobi(number.clone()) {
+            Some(-1) => return proth_with_base(&proth, base),
+            Some(0) => return *number == BigUint::from(base),
+            _ => {}
+        }
+    }
+    unreachable!()
+}
+pub fn pepin_test(n: u32) -> bool {
+    assert!(n < u32::BITS, "Fermat number index too large");
+    n == 0 || proth_with_base(&SpecialModulus::new(1, 1 << n, true), 3)
+}
+pub fn llr_test(k: u64, n: u32) -> bool {
+    assert!(
+        k % 2 == 1 && (n >= u64::BITS || k < 1 << n),
+        "k must be odd and smaller than 2^n"
+    );
+    let riesel = SpecialModulus::new(k, n, false);
+    let number = &riesel.modulus;
+    if *number < BigUint::from(2u32) || big_is_square(number) {
+        return false;
+    }
+    // Rödseth's criterion: the starting value V_k(P, 1) needs (P - 2 | N) = 1 and (P + 2 | N) = -1.
+    let mut p = 3u32;
+    loop {
+        let minus = BigUint::from(p - 2).jacobi(number.clone());
+        let plus = BigUint::from(p + 2).jacobi(number.clone());
+        if minus == Some(0) || plus == Some(0) {
+            let factor = BigUint::from(if minus == Some(0) { p - 2 } else { p + 2 });
+            if !(factor % number).is_zero() {
+                return false;
+            }
+        } else if minus == Some(1) && plus == Some(-1) {
+            break;
+        }
+        p += 1;
+    }
+    let two = riesel.reduce(BigUint::from(2u32));
+    let mut u = riesel.lucas_v(&riesel.reduce(BigUint::from(p)), k);
+    for _ in 2..n {
+        u = riesel.sub(riesel.square(&u), &two);
+    }
+    u.is_zero()
+}
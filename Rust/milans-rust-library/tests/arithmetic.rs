@@ -0,0 +1,117 @@
+use milans_rust_library::math::arithmetic::{ArithmeticFunctions, mobius_table, totient_table};
+use milans_rust_library::math::factor::Factorize;
+use milans_rust_library::math::primality::PowMod;
+use num_bigint::BigUint;
+const LIMIT: u64 = 10_000;
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+fn prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut d = 2;
+    while d * d <= n {
+        while n.is_multiple_of(d) {
+            factors.push(d);
+            n /= d;
+        }
+        d += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+fn brute_mobius(n: u64) -> i8 {
+    let factors = prime_factors(n);
+    if factors.windows(2).any(|pair| pair[0] == pair[1]) {
+        0
+    } else if factors.len().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    }
+}
+// lambda is the exponent of the unit group: every unit satisfies a^lambda = 1, and for each
+// prime q dividing lambda some unit fails a^(lambda / q) = 1.
+fn check_carmichael(n: u64, lambda: u64) {
+    let units: Vec<u64> = (1..=n).filter(|a| gcd(*a, n) == 1).map(|a| a % n).collect();
+    let holds = |m: u64| units.iter().all(|a| a.pow_mod(m as u128, n) == 1 % n);
+    assert!(holds(lambda), "{n}");
+    for prime in prime_factors(lambda) {
+        assert!(!holds(lambda / prime), "{n}");
+    }
+}
+#[test]
+fn arithmetic_functions_match_brute_force() {
+    let size = LIMIT as usize;
+    let (mut count, mut sigma, mut sigma_two) =
+        (vec![0u64; size], vec![0u64; size], vec![0u64; size]);
+    for d in 1..LIMIT {
+        for multiple in (d..LIMIT).step_by(d as usize) {
+            count[multiple as usize] += 1;
+            sigma[multiple as usize] += d;
+            sigma_two[multiple as usize] += d * d;
+        }
+    }
+    let totients = totient_table(size - 1);
+    let mobius = mobius_table(size - 1);
+    assert_eq!(totients[0], 0);
+    assert_eq!(mobius[0], 0);
+    for n in 1..LIMIT {
+        let totient = (1..=n).filter(|k| gcd(*k, n) == 1).count() as u64;
+        let factors = prime_factors(n);
+        let mut distinct = factors.clone();
+        distinct.dedup();
+        assert_eq!(n.euler_totient(), totient, "{n}");
+        assert_eq!(totients[n as usize], totient, "{n}");
+        assert_eq!(n.mobius(), brute_mobius(n), "{n}");
+        assert_eq!(mobius[n as usize], brute_mobius(n), "{n}");
+        assert_eq!(n.divisor_count(), count[n as usize], "{n}");
+        assert_eq!(n.divisor_sigma(0), count[n as usize], "{n}");
+        assert_eq!(n.divisor_sigma(1), sigma[n as usize], "{n}");
+        assert_eq!(n.divisor_sigma(2), sigma_two[n as usize], "{n}");
+        assert_eq!(n.distinct_prime_factors(), distinct.len() as u32, "{n}");
+        assert_eq!(n.prime_factor_count(), factors.len() as u32, "{n}");
+        if n < 2_000 {
+            check_carmichael(n, n.carmichael_lambda());
+        } else {
+            assert!(totient.is_multiple_of(n.carmichael_lambda()), "{n}");
+        }
+    }
+}
+#[test]
+fn arithmetic_functions_agree_across_types() {
+    for n in (1..LIMIT).step_by(7) {
+        let factorization = n.factorize();
+        let negative = -(n as i64);
+        assert_eq!(negative.euler_totient() as u64, n.euler_totient(), "{n}");
+        assert_eq!(
+            negative.carmichael_lambda() as u64,
+            n.carmichael_lambda(),
+            "{n}"
+        );
+        assert_eq!(negative.mobius(), n.mobius(), "{n}");
+        assert_eq!(negative.divisor_sigma(1) as u64, n.divisor_sigma(1), "{n}");
+        assert_eq!(negative.prime_factor_count(), n.prime_factor_count(), "{n}");
+        assert_eq!(factorization.euler_totient(), n.euler_totient(), "{n}");
+        assert_eq!(factorization.divisor_count(), n.divisor_count(), "{n}");
+        assert_eq!(
+            BigUint::from(n).euler_totient(),
+            BigUint::from(n.euler_totient())
+        );
+        assert_eq!(
+            (n as u128).carmichael_lambda(),
+            n.carmichael_lambda() as u128
+        );
+    }
+}
+#[test]
+#[should_panic(expected = "arithmetic functions are undefined at 0")]
+fn totient_of_zero_panics() {
+    0u64.euler_totient();
+}
+#[test]
+#[should_panic(expected = "arithmetic functions are undefined at 0")]
+fn mobius_of_zero_panics() {
+    0i32.mobius();
+}
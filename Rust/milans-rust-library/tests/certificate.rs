@@ -0,0 +1,167 @@
+use milans_rust_library::math::certificate::{
+    CertifiedFactor, PrimalityCertificate, verify_certificate,
+};
+use num_bigint::BigUint;
+// Generating these takes close to a minute each, almost all of it in ECM failing on the
+// composite cofactor of n - 1, so the default tests start from the certificates' text.
+const BLS_MINUS: &str = "\
+bls-minus 15196142303368490761557562339808723198246208383856405710305018737320651292297070841765349401169603235676161 3
+factor 2 150 7
+trial 2
+factor 3 1 2
+trial 3
+factor 5 1 7
+trial 5
+";
+const BLS_PLUS: &str = "\
+bls-plus 18000411332470130091029239273686733692514589241710207229367018712882631583570037236896637983936173286805953130094051804118531003157315583 5 3
+factor 2 250 5
+trial 2
+factor 3 1 9
+trial 3
+factor 797 1 3
+trial 797
+";
+const POCKLINGTON: &str = "68064009489132583819385966053673339493702478871452997420938795269148920541048616745879799770524945984234641742156901433333896068498194433";
+fn kind(certificate: &PrimalityCertificate) -> &'static str {
+    match certificate {
+        PrimalityCertificate::TrialDivision { .. } => "trial",
+        PrimalityCertificate::Pratt { .. } => "pratt",
+        PrimalityCertificate::Pocklington { .. } => "pocklington",
+        PrimalityCertificate::BlsMinus { .. } => "bls-minus",
+        PrimalityCertificate::BlsPlus { .. } => "bls-plus",
+    }
+}
+fn round_trip(n: &BigUint, expected: &str) -> PrimalityCertificate {
+    let certificate = PrimalityCertificate::generate(n).unwrap();
+    assert_eq!(kind(&certificate), expected, "{n}");
+    assert_eq!(certificate.prime(), n);
+    assert!(verify_certificate(&certificate), "{n}");
+    let parsed: PrimalityCertificate = certificate.to_string().parse().unwrap();
+    assert_eq!(parsed, certificate);
+    assert!(verify_certificate(&parsed), "{n}");
+    certificate
+}
+#[test]
+fn generated_certificates_round_trip() {
+    round_trip(&BigUint::from(4_294_967_291u64), "trial");
+    round_trip(&BigUint::from((1u64 << 61) - 1), "pratt");
+    round_trip(&((BigUint::from(1u32) << 89u32) - 1u32), "pratt");
+    round_trip(&((BigUint::from(1u32) << 127u32) - 1u32), "pratt");
+    round_trip(&POCKLINGTON.parse().unwrap(), "pocklington");
+}
+#[test]
+fn bls_certificates_round_trip() {
+    for (text, expected) in [(BLS_MINUS, "bls-minus"), (BLS_PLUS, "bls-plus")] {
+        let certificate: PrimalityCertificate = text.parse().unwrap();
+        assert_eq!(kind(&certificate), expected);
+        assert!(verify_certificate(&certificate));
+        assert_eq!(certificate.to_string(), text);
+    }
+}
+#[test]
+#[ignore]
+fn bls_certificates_are_generated() {
+    for (text, expected) in [(BLS_MINUS, "bls-minus"), (BLS_PLUS, "bls-plus")] {
+        let fixture: PrimalityCertificate = text.parse().unwrap();
+        round_trip(fixture.prime(), expected);
+    }
+}
+fn factors(certificate: &mut PrimalityCertificate) -> &mut Vec<CertifiedFactor> {
+    match certificate {
+        PrimalityCertificate::TrialDivision { .. } => panic!("no factors"),
+        PrimalityCertificate::Pratt { factors, .. }
+        | PrimalityCertificate::Pocklington { factors, .. }
+        | PrimalityCertificate::BlsMinus { factors, .. }
+        | PrimalityCertificate::BlsPlus { factors, .. } => factors,
+    }
+}
+fn prime(certificate: &mut PrimalityCertificate) -> &mut BigUint {
+    match certificate {
+        PrimalityCertificate::TrialDivision { prime }
+        | PrimalityCertificate::Pratt { prime, .. }
+        | PrimalityCertificate::Pocklington { prime, .. }
+        | PrimalityCertificate::BlsMinus { prime, .. }
+        | PrimalityCertificate::BlsPlus { prime, .. } => prime,
+    }
+}
+#[test]
+fn tampered_certificates_fail() {
+    let pratt = PrimalityCertificate::generate(&BigUint::from((1u64 << 61) - 1)).unwrap();
+    let pocklington = PrimalityCertificate::generate(&POCKLINGTON.parse().unwrap()).unwrap();
+    let bls_minus: PrimalityCertificate = BLS_MINUS.parse().unwrap();
+    let bls_plus: PrimalityCertificate = BLS_PLUS.parse().unwrap();
+    for certificate in [pratt, pocklington, bls_minus, bls_plus] {
+        let mut tampered = certificate.clone();
+        factors(&mut tampered)[0].witness = BigUint::from(0u32);
+        assert!(!verify_certificate(&tampered), "{certificate}");
+        let mut tampered = certificate.clone();
+        *prime(&mut tampered) += 2u32;
+        assert!(!verify_certificate(&tampered), "{certificate}");
+        let mut tampered = certificate.clone();
+        factors(&mut tampered).remove(0);
+        assert!(!verify_certificate(&tampered), "{certificate}");
+        // A factor whose own certificate proves a different prime.
+        let mut tampered = certificate.clone();
+        factors(&mut tampered)[0].prime += 1u32;
+        assert!(!verify_certificate(&tampered), "{certificate}");
+        let mut tampered = certificate.clone();
+        factors(&mut tampered)[0].exponent = 0;
+        assert!(!verify_certificate(&tampered), "{certificate}");
+    }
+    let composite: PrimalityCertificate = "trial 91".parse().unwrap();
+    assert!(!verify_certificate(&composite));
+    let nested: PrimalityCertificate = "pratt 7 2\nfactor 2 1 3\ntrial 2\nfactor 4 1 3\ntrial 4\n"
+        .parse()
+        .unwrap();
+    assert!(!verify_certificate(&nested));
+}
+#[test]
+fn composites_have_no_certificate() {
+    for n in [
+        0u64,
+        1,
+        4,
+        91,
+        561,
+        3_215_031_751,
+        341_550_071_728_321,
+        1 << 40,
+    ] {
+        assert_eq!(
+            PrimalityCertificate::generate(&BigUint::from(n)),
+            None,
+            "{n}"
+        );
+    }
+    let fermat: BigUint = (BigUint::from(1u32) << 64u32) + 1u32;
+    assert_eq!(PrimalityCertificate::generate(&fermat), None);
+    let semiprime =
+        ((BigUint::from(1u32) << 61u32) - 1u32) * ((BigUint::from(1u32) << 89u32) - 1u32);
+    assert_eq!(PrimalityCertificate::generate(&semiprime), None);
+}
+#[test]
+fn malformed_text_is_rejected() {
+    let error = |text: &str| {
+        text.parse::<PrimalityCertificate>()
+            .unwrap_err()
+            .to_string()
+    };
+    let line = |number: usize| format!("invalid primality certificate at line {number}");
+    assert_eq!(error(""), line(1));
+    assert_eq!(error("trial"), line(1));
+    assert_eq!(error("trial x"), line(1));
+    assert_eq!(error("trial 7 1"), line(1));
+    assert_eq!(error("ecpp 7 1"), line(1));
+    assert_eq!(error("pratt 7"), line(1));
+    assert_eq!(error("pratt 7 x"), line(1));
+    assert_eq!(error("bls-plus 7 1"), line(1));
+    assert_eq!(error("pratt 7 1"), line(2));
+    assert_eq!(error("pratt 7 1\nfactor 2 1"), line(2));
+    assert_eq!(error("pratt 7 1\nfactor 2 one 3\ntrial 2"), line(2));
+    assert_eq!(error("pratt 7 1\nwitness 2 1 3\ntrial 2"), line(2));
+    assert_eq!(error("pratt 7 1\nfactor 2 1 3"), line(3));
+    assert_eq!(error("pratt 7 2\nfactor 2 1 3\ntrial 2"), line(4));
+    assert_eq!(error("trial 7\ntrial 7"), line(2));
+    assert!("trial 7\n\n".parse::<PrimalityCertificate>().is_ok());
+}
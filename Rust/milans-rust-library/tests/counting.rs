@@ -0,0 +1,55 @@
+use milans_rust_library::math::counting::prime_count;
+use milans_rust_library::math::sieve::primes_up_to;
+const PI_POWERS_OF_TEN: [u64; 16] = [
+    0,
+    4,
+    25,
+    168,
+    1_229,
+    9_592,
+    78_498,
+    664_579,
+    5_761_455,
+    50_847_534,
+    455_052_511,
+    4_118_054_813,
+    37_607_912_018,
+    346_065_536_839,
+    3_204_941_750_802,
+    29_844_570_422_669,
+];
+fn cross_check_sieve(limit: u64, step: usize) {
+    let primes = primes_up_to(limit);
+    for x in (0..limit).step_by(step) {
+        let count = primes.partition_point(|prime| *prime <= x);
+        assert_eq!(prime_count(x), count as u64, "{x}");
+    }
+}
+#[test]
+fn prime_count_matches_sieve() {
+    cross_check_sieve(10_000, 1);
+    cross_check_sieve(1_000_000, 997);
+}
+#[test]
+#[ignore]
+fn prime_count_matches_sieve_below_one_million() {
+    cross_check_sieve(1_000_000, 1);
+}
+fn check_powers_of_ten(exponents: std::ops::Range<usize>) {
+    for exponent in exponents {
+        assert_eq!(
+            prime_count(10u64.pow(exponent as u32)),
+            PI_POWERS_OF_TEN[exponent],
+            "10^{exponent}"
+        );
+    }
+}
+#[test]
+fn prime_count_powers_of_ten() {
+    check_powers_of_ten(0..15);
+}
+#[test]
+#[ignore]
+fn prime_count_large_powers_of_ten() {
+    check_powers_of_ten(15..16);
+}
@@ -0,0 +1,75 @@
+use milans_rust_core::random::{Rng, SplitMix64};
+use milans_rust_library::math::dlog::{baby_step_giant_step, pohlig_hellman, pollard_rho_log};
+use milans_rust_library::math::primality::{MillerRabinTest, PowMod};
+// The smallest q >= start with q and 2q + 1 both prime, so 4 generates the subgroup of order q.
+fn safe_prime_above(start: u64) -> (u64, u64) {
+    (start..)
+        .find(|q| q.miller_rabin_test() && (2 * q + 1).miller_rabin_test())
+        .map(|q| (2 * q + 1, q))
+        .unwrap()
+}
+#[test]
+fn baby_step_giant_step_round_trips() {
+    let mut rng = SplitMix64::new(16);
+    let (modulus, order) = safe_prime_above(1 << 30);
+    for _ in 0..20 {
+        let exponent = rng.below(order);
+        let target = 4u64.pow_mod(exponent as u128, modulus);
+        assert_eq!(
+            baby_step_giant_step(4, target, modulus, order),
+            Some(exponent)
+        );
+    }
+    assert_eq!(baby_step_giant_step(3u32, 1, 7, 6), Some(0));
+    // 5 is a non-residue mod 23, so it is not a power of 4.
+    assert_eq!(baby_step_giant_step(4u64, 5, 23, 11), None);
+}
+#[test]
+fn pollard_rho_log_round_trips() {
+    let mut rng = SplitMix64::new(16);
+    let (modulus, order) = safe_prime_above(1 << 41);
+    for _ in 0..3 {
+        let exponent = rng.below(order);
+        let target = 4u64.pow_mod(exponent as u128, modulus);
+        assert_eq!(
+            pollard_rho_log(4, target, modulus, order, &mut rng),
+            Some(exponent)
+        );
+    }
+    assert_eq!(pollard_rho_log(4u64, 3, 23, 0, &mut rng), None);
+    // With order 11 about one walk in 11 ends in a useless collision, exercising the restarts.
+    for seed in 0..100 {
+        let mut rng = SplitMix64::new(seed);
+        for exponent in 0..11 {
+            let target = 4u64.pow_mod(exponent as u128, 23);
+            assert_eq!(
+                pollard_rho_log(4, target, 23, 11, &mut rng),
+                Some(exponent),
+                "{seed}"
+            );
+        }
+    }
+}
+#[test]
+fn pohlig_hellman_round_trips() {
+    let mut rng = SplitMix64::new(16);
+    // 7 * 2^20 + 1 has a smooth group order, the safe prime has a factor above 2^40.
+    let smooth = (7 << 20) + 1;
+    let (safe, _) = safe_prime_above(1 << 41);
+    for (modulus, base) in [(smooth, 3u64), (safe, 5)] {
+        let order = modulus - 1;
+        for _ in 0..5 {
+            let exponent = rng.below(order);
+            let target = base.pow_mod(exponent as u128, modulus);
+            let result = pohlig_hellman(base, target, modulus, order).unwrap();
+            assert_eq!(base.pow_mod(result as u128, modulus), target);
+            assert!(result < order);
+        }
+    }
+    let exponent = rng.next_u64() as u128;
+    let modulus = (1u128 << 89) - 1;
+    let target = 3u128.pow_mod(exponent, modulus);
+    let result = pohlig_hellman(3, target, modulus, modulus - 1).unwrap();
+    assert_eq!(3u128.pow_mod(result, modulus), target);
+    assert_eq!(pohlig_hellman(4u64, 5, 23, 22), None);
+}
@@ -0,0 +1,182 @@
+use milans_rust_core::random::{Rng, SplitMix64};
+use milans_rust_library::math::factor::{Factorization, Factorize, ecm, qs};
+use milans_rust_library::math::primality::MillerRabinTest;
+use milans_rust_library::math::primegen::RandomPrime;
+use num_bigint::BigUint;
+fn check_u64(n: u64) {
+    let factors = n.factorize();
+    let mut product = 1u64;
+    for (prime, exponent) in factors.iter() {
+        assert!(prime.miller_rabin_test(), "{prime} in {n}");
+        product *= prime.pow(*exponent);
+    }
+    assert_eq!(product, n);
+}
+fn check_u128(n: u128) -> Factorization<u128> {
+    let factors = n.factorize();
+    let mut product = 1u128;
+    for (prime, exponent) in factors.iter() {
+        assert!(prime.miller_rabin_test(), "{prime} in {n}");
+        product *= prime.pow(*exponent);
+    }
+    assert_eq!(product, n);
+    factors
+}
+#[test]
+fn factorize_small_numbers() {
+    for n in 1..100_000 {
+        check_u64(n);
+    }
+    assert_eq!(1u64.factorize(), Factorization::new());
+    assert_eq!(97u8.factorize().exponent(&97), 1);
+    assert_eq!(u64::MAX.factorize().len(), 7);
+}
+#[test]
+fn factorize_random_numbers() {
+    let mut rng = SplitMix64::new(9);
+    for _ in 0..2_000 {
+        check_u64(rng.next_u64());
+    }
+    for _ in 0..20 {
+        check_u128(rng.next_u128());
+    }
+}
+fn check_u128_semiprime(small_bits: u32, large_bits: u32, rng: &mut SplitMix64) {
+    let (p, q) = (
+        u128::random_prime(small_bits, rng),
+        u128::random_prime(large_bits, rng),
+    );
+    let factors = check_u128(p * q);
+    assert_eq!(factors.primes().copied().collect::<Vec<_>>(), {
+        let mut expected = vec![p.min(q), p.max(q)];
+        expected.dedup();
+        expected
+    });
+}
+#[test]
+fn factorize_semiprimes() {
+    let mut rng = SplitMix64::new(10);
+    for _ in 0..200 {
+        let (p, q) = (
+            u64::random_prime(32, &mut rng),
+            u64::random_prime(32, &mut rng),
+        );
+        check_u64(p * q);
+        assert_eq!((p * q).factorize().exponent(&p), 1 + (p == q) as u32);
+    }
+    for _ in 0..3 {
+        check_u128_semiprime(40, 88, &mut rng);
+    }
+}
+// Two 64-bit factors take Pollard rho about 2^32 steps each.
+#[test]
+#[ignore]
+fn factorize_balanced_u128_semiprimes() {
+    let mut rng = SplitMix64::new(10);
+    for _ in 0..3 {
+        check_u128_semiprime(64, 64, &mut rng);
+    }
+}
+#[test]
+fn factorize_big_numbers() {
+    let mut rng = SplitMix64::new(11);
+    let primes: Vec<BigUint> = [24, 40, 64, 80]
+        .iter()
+        .map(|bits| BigUint::random_prime(*bits, &mut rng))
+        .collect();
+    let n = primes.iter().product::<BigUint>() * 1024u32 * 27u32;
+    let factors = n.clone().factorize();
+    let mut product = BigUint::from(1u32);
+    for (prime, exponent) in factors.iter() {
+        assert!(prime.clone().miller_rabin_test());
+        product *= prime.pow(*exponent);
+    }
+    assert_eq!(product, n);
+    assert_eq!(factors.len(), 6);
+}
+#[test]
+fn factorize_perfect_powers() {
+    let mersenne = (BigUint::from(1u32) << 89u32) - 1u32;
+    let factors = mersenne.pow(5).factorize();
+    assert_eq!(factors.len(), 1);
+    assert_eq!(factors.exponent(&mersenne), 5);
+    // 1031 is the smallest prime left after trial division and 1031^41 has 411 bits.
+    let factors = (BigUint::from(1031u32).pow(41) * 1024u32).factorize();
+    assert_eq!(factors.exponent(&BigUint::from(1031u32)), 41);
+    assert_eq!(factors.exponent(&BigUint::from(2u32)), 10);
+}
+#[test]
+fn factorize_zero_and_negative() {
+    assert!(0u64.factorize().is_zero());
+    assert!(0i32.factorize().is_empty());
+    assert!(0u128.factorize().is_zero());
+    assert!(0i128.factorize().is_zero());
+    assert!(BigUint::from(0u32).factorize().is_zero());
+    assert_eq!(12u8.factorize().sign(), 1);
+    let factors = (-12i32).factorize();
+    assert_eq!(factors.sign(), -1);
+    assert_eq!(factors.exponent(&2), 2);
+    assert_eq!(factors.exponent(&3), 1);
+    assert_eq!((-1i64).factorize().sign(), -1);
+    assert!((-1i64).factorize().is_empty());
+    assert_eq!(i128::MIN.factorize().exponent(&2), 127);
+    assert_eq!(i128::MIN.factorize().sign(), -1);
+}
+fn ecm_semiprime(digits: u32, rng: &mut SplitMix64) -> (BigUint, BigUint) {
+    let bits = (digits as f64 * 10f64.log2()) as u32;
+    let p = BigUint::random_prime(bits, rng);
+    let q = BigUint::random_prime(2 * bits, rng);
+    assert_eq!(p.to_string().len() as u32, digits);
+    (p, q)
+}
+fn ecm_bounds(digits: u32) -> (u64, usize) {
+    let (_, b1, curves) = ecm::ECM_SCHEDULE
+        .into_iter()
+        .find(|(factor_digits, _, _)| *factor_digits >= digits)
+        .unwrap();
+    (b1, curves)
+}
+fn check_ecm(digits: u32, seed: u64) {
+    let mut rng = SplitMix64::new(seed);
+    let (b1, curves) = ecm_bounds(digits);
+    let (p, q) = ecm_semiprime(digits, &mut rng);
+    let n = &p * &q;
+    let factor = ecm::ecm_find_factor(&n, b1, 100 * b1, curves, &mut rng).unwrap();
+    assert!(factor == p || factor == q, "{factor} does not divide {n}");
+}
+#[test]
+fn ecm_finds_twenty_digit_factor() {
+    check_ecm(20, 12);
+}
+#[test]
+fn ecm_finds_twenty_five_digit_factor_on_known_curve() {
+    let (b1, _) = ecm_bounds(25);
+    let (p, q) = ecm_semiprime(25, &mut SplitMix64::new(13));
+    let n = &p * &q;
+    // Seed 163 draws a curve whose group order modulo p is smooth for the 25-digit bounds.
+    let factor = ecm::ecm_find_factor(&n, b1, 100 * b1, 1, &mut SplitMix64::new(163)).unwrap();
+    assert_eq!(factor, p);
+}
+#[test]
+#[ignore]
+fn ecm_finds_twenty_five_digit_factor() {
+    check_ecm(25, 13);
+}
+fn check_siqs(digits: usize, bits: u32, seed: u64) {
+    let mut rng = SplitMix64::new(seed);
+    let p = BigUint::random_prime(bits, &mut rng);
+    let q = BigUint::random_prime(bits + 1, &mut rng);
+    let n = &p * &q;
+    assert_eq!(n.to_string().len(), digits);
+    let factor = qs::siqs_find_factor(&n).unwrap();
+    assert!(factor == p || factor == q, "{factor} does not divide {n}");
+}
+#[test]
+fn siqs_splits_fifty_digit_semiprime() {
+    check_siqs(50, 82, 15);
+}
+#[test]
+#[ignore]
+fn siqs_splits_eighty_digit_semiprime() {
+    check_siqs(80, 132, 14);
+}
@@ -0,0 +1,43 @@
+use milans_rust_library::math::modint::{Mod, ModInt};
+use num::{One, Zero};
+#[test]
+fn mod_int_arithmetic() {
+    let a = ModInt::new(5u64, 7);
+    let b = ModInt::new(4u64, 7);
+    assert_eq!((a + b).value(), 2);
+    assert_eq!((b - a).value(), 6);
+    assert_eq!((a * b).value(), 6);
+    assert_eq!((a / b * b), a);
+    assert_eq!((-a).value(), 2);
+    assert!((a - a).is_zero());
+    assert_eq!(a.pow(u128::MAX).value(), 5u64.pow(3) % 7);
+    assert_eq!(ModInt::new(0u64, 7), ModInt::new(7, 7));
+    assert_eq!(ModInt::new(-3i64, 7).value(), 4);
+    assert_eq!(ModInt::new(3u64, 9).inverse(), None);
+}
+#[test]
+fn mod_int_identities() {
+    for modulus in [1u64, 2, 7, 1 << 40] {
+        let (zero, one) = (ModInt::zero(modulus), ModInt::one(modulus));
+        assert!(zero.is_zero() && zero.modulus() == modulus);
+        let a = ModInt::new(modulus / 2 + 1, modulus);
+        assert_eq!(a + zero, a);
+        assert_eq!(a * one, a);
+        assert_eq!(a.pow(0), one);
+    }
+    assert_eq!(ModInt::one(1i32).value(), 0);
+    assert_eq!(ModInt::one(7i32), ModInt::new(-6, 7));
+}
+#[test]
+#[should_panic(expected = "different moduli")]
+fn mod_int_rejects_mixed_moduli() {
+    let _ = ModInt::new(1u64, 7) + ModInt::new(1, 5);
+}
+#[test]
+fn const_mod_arithmetic() {
+    type M = Mod<1_000_000_007>;
+    assert_eq!((M::one() + M::one()).value(), 2);
+    assert_eq!(M::zero() - M::one(), M::new(1_000_000_006));
+    assert_eq!(M::new(2).pow(1_000_000_006), M::one());
+    assert_eq!(M::new(3) / M::new(3), M::one());
+}
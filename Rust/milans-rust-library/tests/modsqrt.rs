@@ -0,0 +1,97 @@
+use milans_rust_core::random::{Rng, SplitMix64};
+use milans_rust_library::math::modsqrt::SqrtMod;
+use milans_rust_library::math::primality::MillerRabinTest;
+use num_bigint::BigUint;
+fn brute_roots(a: u64, modulus: u64) -> Vec<u64> {
+    (0..modulus)
+        .filter(|r| r * r % modulus == a % modulus)
+        .collect()
+}
+fn check_sqrt_mod(a: u64, modulus: u64) {
+    let mut roots: Vec<u64> = a.sqrt_mod(modulus).collect();
+    roots.sort();
+    assert_eq!(roots, brute_roots(a, modulus), "{a} {modulus}");
+}
+#[test]
+fn sqrt_mod_matches_brute_force() {
+    for modulus in 1..300 {
+        for a in 0..modulus {
+            check_sqrt_mod(a, modulus);
+        }
+    }
+    for modulus in [512, 1024, 2048, 3 * 1024, 729, 2 * 3125, 4 * 343] {
+        for a in 0..modulus {
+            check_sqrt_mod(a, modulus);
+        }
+    }
+}
+#[test]
+fn sqrt_mod_prime_power_matches_brute_force() {
+    for (prime, exponent) in [(2u64, 1), (2, 2), (2, 3), (2, 10), (3, 6), (5, 4), (7, 3)] {
+        let modulus = prime.pow(exponent);
+        for a in 0..modulus {
+            let mut roots: Vec<u64> = a.sqrt_mod_prime_power(prime, exponent).collect();
+            roots.sort();
+            assert_eq!(roots, brute_roots(a, modulus), "{a} {prime}^{exponent}");
+        }
+    }
+}
+#[test]
+fn non_residues_have_no_roots() {
+    for prime in [3u64, 7, 11, 1009] {
+        for a in 1..prime {
+            if brute_roots(a, prime).is_empty() {
+                assert_eq!(a.sqrt_mod(prime).count(), 0, "{a} {prime}");
+                assert_eq!(a.tonelli_shanks(prime), None, "{a} {prime}");
+                assert_eq!(a.cipolla(prime), None, "{a} {prime}");
+            }
+        }
+    }
+    assert_eq!(3u64.sqrt_mod(8).next(), None);
+    assert_eq!(2u64.sqrt_mod(15).next(), None);
+}
+#[test]
+fn prime_roots_square_back() {
+    for prime in (2..3000u64).filter(|p| p.miller_rabin_test()) {
+        for a in 0..prime {
+            let residue = !brute_roots(a, prime).is_empty();
+            for root in [a.tonelli_shanks(prime), a.cipolla(prime)] {
+                assert_eq!(root.is_some(), residue, "{a} {prime}");
+                if let Some(root) = root {
+                    assert_eq!(root * root % prime, a, "{a} {prime}");
+                }
+            }
+        }
+    }
+    let mut rng = SplitMix64::new(15);
+    let prime = (1u128 << 127) - 1;
+    for _ in 0..100 {
+        let x = BigUint::from(rng.next_u128());
+        let big_prime = BigUint::from(prime);
+        let a = &x * &x % &big_prime;
+        for root in [
+            a.clone().tonelli_shanks(big_prime.clone()),
+            a.clone().cipolla(big_prime.clone()),
+        ] {
+            let root = root.unwrap();
+            assert_eq!(&root * &root % &big_prime, a);
+        }
+        let x = rng.next_u64() % ((1 << 61) - 1);
+        let a = (x as u128 * x as u128 % ((1 << 61) - 1)) as u64;
+        let root = a.tonelli_shanks((1 << 61) - 1).unwrap();
+        assert_eq!((root as u128 * root as u128 % ((1 << 61) - 1)) as u64, a);
+    }
+}
+#[test]
+fn composite_moduli_terminate() {
+    for modulus in (9..600u64).step_by(2).filter(|n| !n.miller_rabin_test()) {
+        for a in 0..modulus {
+            for root in [a.tonelli_shanks(modulus), a.cipolla(modulus)]
+                .into_iter()
+                .flatten()
+            {
+                assert_eq!(root * root % modulus, a, "{a} {modulus}");
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use milans_rust_core::random::{Rng, SplitMix64};
+use milans_rust_library::math::modular::{JacobiSymbol, Modular, QuadraticResidue};
+use num_bigint::{BigInt, BigUint};
+macro_rules! check_bezout {
+    ($rng:expr,$($type:ty),+) => {
+        $({
+            let mut values = vec![0, 1, 2, <$type>::MAX, <$type>::MAX - 1, <$type>::MIN + 1];
+            // MIN is left out of the signed types, where its gcd with 0 overflows.
+            values.extend(
+                (0..40)
+                    .map(|_| $rng.next_u128() as $type)
+                    .filter(|value| *value != <$type>::MIN),
+            );
+            for a in values.iter().copied() {
+                for b in values.iter().copied() {
+                    let (gcd, x, y) = a.egcd(b);
+                    let bezout = BigInt::from(a) * BigInt::from(x) + BigInt::from(b) * BigInt::from(y);
+                    assert_eq!(bezout, BigInt::from(gcd), "{} {a} {b}", stringify!($type));
+                    assert_eq!(a.binary_gcd(b), gcd, "{} {a} {b}", stringify!($type));
+                    assert!(BigInt::from(gcd) >= BigInt::from(0), "{} {a} {b}", stringify!($type));
+                    if gcd != 0 as $type {
+                        assert_eq!(a % gcd, 0 as $type);
+                        assert_eq!(b % gcd, 0 as $type);
+                    }
+                }
+            }
+        })+
+    };
+}
+#[test]
+fn egcd_satisfies_bezout() {
+    let mut rng = SplitMix64::new(16);
+    check_bezout!(
+        rng, u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize
+    );
+    for _ in 0..200 {
+        let common = BigInt::from(rng.next_u64());
+        let a = BigInt::from(rng.next_u128() as i128) * &common;
+        let b = BigInt::from(rng.next_u128() as i128) * &common;
+        let (gcd, x, y) = a.clone().egcd(b.clone());
+        assert_eq!(&a * x + &b * y, gcd);
+        assert_eq!(a.binary_gcd(b), gcd);
+    }
+    let (gcd, x, y) = BigUint::from(240u32).egcd(BigUint::from(46u32));
+    assert_eq!(gcd, BigUint::from(2u32));
+    assert_eq!(240 * x + 46 * y, BigInt::from(2));
+}
+#[test]
+#[should_panic(expected = "gcd overflows the signed type")]
+fn signed_gcd_of_min_overflows() {
+    i8::MIN.binary_gcd(0);
+}
+#[test]
+fn mod_inverse() {
+    assert_eq!(0u32.mod_inverse(7), None);
+    assert_eq!(0i64.mod_inverse(7), None);
+    assert_eq!(BigInt::from(0).mod_inverse(BigInt::from(7)), None);
+    assert_eq!(3u8.mod_inverse(0), None);
+    assert_eq!(4u64.mod_inverse(6), None);
+    assert_eq!((-3i32).mod_inverse(7), Some(2));
+    assert_eq!(3i32.mod_inverse(-7), None);
+    for m in 2..200u64 {
+        for a in 0..m {
+            match a.mod_inverse(m) {
+                Some(inverse) => assert_eq!(a * inverse % m, 1 % m, "{a} {m}"),
+                None => assert_ne!(a.binary_gcd(m), 1, "{a} {m}"),
+            }
+        }
+    }
+}
+#[test]
+fn crt_non_coprime_moduli() {
+    assert_eq!(u64::crt(&[(2, 6), (8, 10)]), Some((8, 30)));
+    assert_eq!(u64::crt(&[(1, 4), (2, 6)]), None);
+    assert_eq!(u64::crt(&[(3, 12), (7, 8), (1, 9)]), None);
+    assert_eq!(u64::crt(&[(3, 12), (7, 8), (3, 9)]), Some((39, 72)));
+    assert_eq!(i32::crt(&[(-1, 4), (5, 6)]), Some((11, 12)));
+    assert_eq!(u64::crt(&[]), Some((0, 1)));
+    assert_eq!(u64::crt(&[(1, 0)]), None);
+    let big = |values: &[(i64, i64)]| {
+        values
+            .iter()
+            .map(|(residue, modulus)| (BigInt::from(*residue), BigInt::from(*modulus)))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        BigInt::crt(&big(&[(2, 6), (8, 10)])),
+        Some((BigInt::from(8), BigInt::from(30)))
+    );
+    assert_eq!(BigInt::crt(&big(&[(1, 4), (2, 6)])), None);
+    for m1 in 1..30u32 {
+        for m2 in 1..30u32 {
+            for r1 in 0..m1 {
+                for r2 in 0..m2 {
+                    let expected = (0..m1 * m2).find(|x| x % m1 == r1 && x % m2 == r2);
+                    let result = u32::crt(&[(r1, m1), (r2, m2)]);
+                    assert_eq!(result.map(|(x, _)| x), expected, "{r1} {m1} {r2} {m2}");
+                    if let Some((_, lcm)) = result {
+                        assert_eq!(lcm, m1 * m2 / m1.binary_gcd(m2));
+                    }
+                }
+            }
+        }
+    }
+}
+#[test]
+fn jacobi_matches_euler_criterion() {
+    let primes: Vec<u32> = (3..1000u32)
+        .step_by(2)
+        .filter(|p| (3..*p).take_while(|d| d * d <= *p).all(|d| p % d != 0))
+        .collect();
+    for p in primes {
+        for a in 0..p {
+            let expected = a.euler_criterion(p);
+            assert_eq!(a.jacobi(p), Some(expected), "{a} {p}");
+            assert_eq!(a.legendre(p), Some(expected), "{a} {p}");
+            assert_eq!(
+                (a as i64 - p as i64).jacobi(p as i64),
+                Some(expected),
+                "{a} {p}"
+            );
+            assert_eq!(BigUint::from(a).jacobi(BigUint::from(p)), Some(expected));
+        }
+    }
+}
+#[test]
+fn jacobi_rejects_even_or_negative_moduli() {
+    assert_eq!(3u32.jacobi(8), None);
+    assert_eq!(3u32.jacobi(0), None);
+    assert_eq!(3i32.jacobi(-7), None);
+    assert_eq!(3i32.jacobi(0), None);
+    assert_eq!(BigUint::from(3u32).jacobi(BigUint::from(10u32)), None);
+    assert_eq!(BigInt::from(3).jacobi(BigInt::from(-5)), None);
+    assert_eq!(5u64.legendre(2), None);
+}
+#[test]
+fn kronecker_extends_jacobi() {
+    for a in -40i64..40 {
+        let two = match a.rem_euclid(8) {
+            1 | 7 => 1,
+            3 | 5 => -1,
+            _ => 0,
+        };
+        assert_eq!(a.kronecker(2), two, "{a}");
+        assert_eq!(a.kronecker(-1), if a < 0 { -1 } else { 1 }, "{a}");
+        assert_eq!(a.kronecker(0), (a == 1 || a == -1) as i8, "{a}");
+        assert_eq!(BigInt::from(a).kronecker(BigInt::from(2)), two, "{a}");
+        assert_eq!(
+            BigInt::from(a).kronecker(BigInt::from(0)),
+            (a.abs() == 1) as i8
+        );
+        for n in (1i64..60).step_by(2) {
+            assert_eq!(a.kronecker(n), a.jacobi(n).unwrap(), "{a} {n}");
+            assert_eq!(a.kronecker(-n), a.kronecker(-1) * a.kronecker(n), "{a} {n}");
+            assert_eq!(a.kronecker(2 * n), two * a.kronecker(n), "{a} {n}");
+        }
+        if a >= 0 {
+            let b = a as u64;
+            assert_eq!(b.kronecker(2), two, "{a}");
+            assert_eq!(b.kronecker(0), (b == 1) as i8, "{a}");
+        }
+    }
+    for n in (1i64..100).step_by(2) {
+        let expected = if n % 4 == 1 { 1 } else { -1 };
+        assert_eq!((-1i64).kronecker(n), expected, "{n}");
+    }
+}
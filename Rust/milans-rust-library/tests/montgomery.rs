@@ -0,0 +1,119 @@
+use milans_rust_core::random::{Rng, SplitMix64};
+use milans_rust_library::math::montgomery::{Montgomery, MontgomeryWord};
+use milans_rust_library::math::primality::PowMod;
+use num::ToPrimitive;
+use num_bigint::BigUint;
+fn odd_u64<R>(rng: &mut R) -> u64
+where
+    R: Rng,
+{
+    match rng.below(4) {
+        0 => 2 * rng.below(64) + 1,
+        1 => u64::MAX - 2 * rng.below(64),
+        _ => rng.next_u64() | 1,
+    }
+}
+fn pow_u128(base: u128, exponent: u128, modulus: u128) -> u128 {
+    let mut result = 1 % modulus;
+    let mut base = base % modulus;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result
+}
+#[test]
+fn montgomery_u64_matches_u128_arithmetic() {
+    let mut rng = SplitMix64::new(1);
+    for _ in 0..20_000 {
+        let modulus = odd_u64(&mut rng);
+        let montgomery = Montgomery::new(modulus).unwrap();
+        let (a, b) = (rng.next_u64(), rng.next_u64());
+        let expected = (a as u128 * b as u128 % modulus as u128) as u64;
+        let (x, y) = (montgomery.to_montgomery(a), montgomery.to_montgomery(b));
+        assert_eq!(montgomery.from_montgomery(x), a % modulus);
+        assert_eq!(montgomery.from_montgomery(montgomery.mul(x, y)), expected);
+        assert_eq!(montgomery.mul_mod(a, b), expected);
+        assert_eq!(montgomery.from_montgomery(montgomery.one()), 1 % modulus);
+        let exponent = rng.next_u128();
+        let expected = pow_u128(a as u128, exponent, modulus as u128) as u64;
+        assert_eq!(montgomery.pow_mod(a, exponent), expected);
+        assert_eq!(a.pow_mod(exponent, modulus), expected);
+    }
+}
+#[test]
+fn montgomery_u128_matches_u128_arithmetic() {
+    let mut rng = SplitMix64::new(2);
+    for _ in 0..20_000 {
+        let modulus = odd_u64(&mut rng) as u128;
+        let montgomery = Montgomery::new(modulus).unwrap();
+        let (a, b) = (rng.next_u128(), rng.next_u128());
+        let expected = (a % modulus) * (b % modulus) % modulus;
+        let (x, y) = (montgomery.to_montgomery(a), montgomery.to_montgomery(b));
+        assert_eq!(montgomery.from_montgomery(x), a % modulus);
+        assert_eq!(montgomery.from_montgomery(montgomery.mul(x, y)), expected);
+        assert_eq!(montgomery.mul_mod(a, b), expected);
+        let exponent = rng.next_u128();
+        assert_eq!(
+            montgomery.pow_mod(a, exponent),
+            pow_u128(a, exponent, modulus)
+        );
+    }
+}
+#[test]
+fn montgomery_u128_matches_big_arithmetic() {
+    let mut rng = SplitMix64::new(3);
+    for _ in 0..5_000 {
+        let modulus = rng.next_u128() | 1 | 1 << 127;
+        let montgomery = Montgomery::new(modulus).unwrap();
+        let (a, b) = (rng.next_u128(), rng.next_u128());
+        let big_modulus = BigUint::from(modulus);
+        let expected = (BigUint::from(a) * BigUint::from(b) % &big_modulus)
+            .to_u128()
+            .unwrap();
+        let (high, low) = a.mul_wide(b);
+        assert_eq!(u128::reduce_wide(high, low, modulus), expected);
+        assert_eq!(
+            (BigUint::from(high) << 128 | BigUint::from(low)),
+            BigUint::from(a) * BigUint::from(b)
+        );
+        let (x, y) = (montgomery.to_montgomery(a), montgomery.to_montgomery(b));
+        assert_eq!(montgomery.from_montgomery(x), a % modulus);
+        assert_eq!(montgomery.from_montgomery(montgomery.mul(x, y)), expected);
+        assert_eq!(montgomery.mul_mod(a, b), expected);
+        let exponent = rng.next_u64() as u128;
+        let expected = BigUint::from(a)
+            .modpow(&BigUint::from(exponent), &big_modulus)
+            .to_u128()
+            .unwrap();
+        assert_eq!(montgomery.pow_mod(a, exponent), expected);
+        assert_eq!(a.pow_mod(exponent, modulus), expected);
+    }
+}
+#[test]
+fn montgomery_newton_inverse() {
+    let mut rng = SplitMix64::new(4);
+    for _ in 0..20_000 {
+        let modulus = odd_u64(&mut rng);
+        let inverse = Montgomery::new(modulus).unwrap().inverse();
+        assert_eq!(modulus.wrapping_mul(inverse), 1);
+        let modulus = rng.next_u128() | 1;
+        let inverse = Montgomery::new(modulus).unwrap().inverse();
+        assert_eq!(modulus.wrapping_mul(inverse), 1);
+    }
+}
+#[test]
+fn montgomery_rejects_even_modulus() {
+    assert!(Montgomery::new(10u64).is_none());
+    assert!(Montgomery::new(1u128 << 100).is_none());
+}
+#[test]
+fn pow_mod_unit_modulus() {
+    assert_eq!(5u64.pow_mod(0, 1), 0);
+    assert_eq!(5u128.pow_mod(0, 1), 0);
+    assert_eq!(Montgomery::new(1u64).unwrap().pow_mod(5, 0), 0);
+}
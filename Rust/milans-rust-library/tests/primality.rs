@@ -0,0 +1,516 @@
+use milans_rust_core::random::{Rng, SplitMix64};
+use milans_rust_library::math::modular::JacobiSymbol;
+use milans_rust_library::math::primality::{
+    BailliePswTest, ExtraStrongLucasTest, FermatTest, FrobeniusTest, MillerRabinTest,
+    MillerRabinWitnesses, PowMod, PrimeNavigation, RandomizedMillerRabinTest, SolovayStrassenTest,
+    StrongLucasTest, is_prime_batch, is_prime_batch_bitset, is_prime_u32, is_prime_u64,
+    lucas_sequence, next_prime_const, pow_mod_u64,
+};
+use milans_rust_library::math::sieve::{primes_in_range, primes_up_to};
+use num_bigint::{BigInt, BigUint};
+macro_rules! check_width {
+    ($n:expr,$expected:expr,$($type:ty),+) => {
+        $(match <$type>::try_from($n) {
+            Ok(n) => assert_eq!(n.miller_rabin_test(), $expected, "{} {n}", stringify!($type)),
+            Err(_) => {}
+        })+
+    };
+}
+fn cross_check_sieve(range: std::ops::Range<u64>) {
+    let primes = primes_in_range(range.clone());
+    let mut primes = primes.iter().peekable();
+    for n in range {
+        let expected = primes.next_if_eq(&&n).is_some();
+        check_width!(
+            n, expected, u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, u128, i128
+        );
+    }
+}
+fn mersenne(exponent: u32) -> BigUint {
+    (BigUint::from(1u32) << exponent) - 1u32
+}
+#[test]
+fn big_miller_rabin() {
+    let mut rng = SplitMix64::new(5);
+    for exponent in [127, 521, 607, 1279] {
+        let prime = mersenne(exponent);
+        assert!(prime.clone().miller_rabin_test());
+        assert!(prime.clone().miller_rabin_test_iter(20));
+        assert!(prime.clone().miller_rabin_test_random(&mut rng, 20));
+        assert!(BigInt::from(prime.clone()).miller_rabin_test_iter(5));
+        assert!(BigInt::from(prime.clone()).miller_rabin_test_random(&mut rng, 5));
+        assert!(!(-BigInt::from(prime)).miller_rabin_test_iter(5));
+    }
+    for exponent in [128, 523, 1277] {
+        let composite = mersenne(exponent);
+        assert!(!composite.clone().miller_rabin_test());
+        assert!(!composite.clone().miller_rabin_test_iter(20));
+        assert!(!composite.miller_rabin_test_random(&mut rng, 20));
+    }
+    let semiprime = mersenne(521) * mersenne(607);
+    assert!(!semiprime.clone().miller_rabin_test_iter(1));
+    assert!(!semiprime.miller_rabin_test_random(&mut rng, 1));
+}
+#[test]
+fn big_miller_rabin_iter_uses_random_bases() {
+    // A composite 2^p - 1 with p prime is a strong pseudoprime to base 2, so one fixed base of 2
+    // would accept these.
+    for exponent in [131, 137, 139, 149] {
+        let composite = mersenne(exponent);
+        assert!(!composite.clone().miller_rabin_test_iter(1), "{exponent}");
+        assert!(
+            !BigInt::from(composite).miller_rabin_test_iter(1),
+            "{exponent}"
+        );
+    }
+    let prime = mersenne(1279);
+    assert!(prime.clone().miller_rabin_test_iter(3));
+    assert!(prime.miller_rabin_test_iter(0));
+}
+#[test]
+fn big_miller_rabin_rejects_pseudoprimes_to_fixed_bases() {
+    // p * (41 (p - 1) + 1) * (101 (p - 1) + 1) with all three factors prime, each 3 mod 4 and
+    // a non-residue mod every prime up to 37, so it is a strong pseudoprime to all those bases.
+    let p = BigUint::from(14_960_832_894_604_841_707u64);
+    let composite = &p * (41u32 * (&p - 1u32) + 1u32) * (101u32 * (&p - 1u32) + 1u32);
+    let exponent = &composite >> 1u32;
+    for base in primes_up_to(37) {
+        assert_eq!(
+            BigUint::from(base).modpow(&exponent, &composite),
+            &composite - 1u32
+        );
+    }
+    assert!(!composite.clone().miller_rabin_test());
+    assert!(!BigInt::from(composite.clone()).miller_rabin_test());
+    assert!(!composite.baillie_psw_test());
+    for exponent in [521, 607, 1279] {
+        let prime = mersenne(exponent);
+        assert!(prime.clone().miller_rabin_test() && prime.baillie_psw_test());
+    }
+}
+#[test]
+fn miller_rabin_matches_sieve() {
+    cross_check_sieve(0..1 << 22);
+    // Above 2^32 the wide types switch to the seven-base Sinclair set.
+    cross_check_sieve((1 << 32) - (1 << 16)..(1 << 32) + (1 << 18));
+    cross_check_sieve(1 << 40..(1 << 40) + (1 << 18));
+    cross_check_sieve(1 << 48..(1 << 48) + (1 << 16));
+}
+#[test]
+#[ignore]
+fn miller_rabin_matches_sieve_below_2_32() {
+    cross_check_sieve(0..1 << 32);
+}
+#[test]
+fn miller_rabin_rejects_strong_pseudoprimes_above_2_32() {
+    // Smallest strong pseudoprimes to the bases 2..=7, 2..=11, 2..=13 and 2..=17.
+    for n in [3215031751u64, 2152302898747, 3474749660383, 341550071728321] {
+        check_width!(n, false, u64, i64, usize, isize, u128, i128);
+        assert!(!BigUint::from(n).miller_rabin_test(), "BigUint {n}");
+    }
+}
+#[test]
+fn nth_prime_stops_at_type_bound() {
+    assert_eq!(u8::nth_prime(0), None);
+    assert_eq!(u8::nth_prime(1), Some(2));
+    assert_eq!(u8::nth_prime(54), Some(251));
+    assert_eq!(u8::nth_prime(55), None);
+    assert_eq!(u8::nth_prime(10_000_000_000), None);
+    assert_eq!(i8::nth_prime(31), Some(127));
+    assert_eq!(i8::nth_prime(32), None);
+    assert_eq!(u16::nth_prime(6542), Some(65521));
+    assert_eq!(u16::nth_prime(6543), None);
+    assert_eq!(u64::nth_prime(1_000_000), Some(15_485_863));
+}
+#[test]
+fn baillie_psw_rejects_pseudoprimes() {
+    // Strong pseudoprimes to base 2, then strong Lucas pseudoprimes.
+    for n in [2047u64, 3277, 4033, 3215031751, 5459, 5777] {
+        assert!(!n.baillie_psw_test(), "{n}");
+        assert!(!(n as u128).baillie_psw_test(), "u128 {n}");
+        assert!(!BigUint::from(n).baillie_psw_test(), "BigUint {n}");
+    }
+    // 1093^2 and 3511^2 are strong pseudoprimes to base 2.
+    for root in [3u64, 1093, 3511, 65521, 4294967291] {
+        assert!(!(root * root).baillie_psw_test(), "{root}^2");
+    }
+    let square = BigUint::from(18446744073709551557u64).pow(2);
+    assert!(!square.clone().baillie_psw_test());
+    assert!(!(square * 101u32).baillie_psw_test());
+    assert!(((1u128 << 89) - 1).baillie_psw_test());
+    assert!(!(((1u128 << 61) - 1) * ((1 << 31) - 1)).baillie_psw_test());
+    assert!(mersenne(521).baillie_psw_test());
+    assert!(!mersenne(523).baillie_psw_test());
+    assert!(!(-7i32).baillie_psw_test());
+}
+#[test]
+fn baillie_psw_matches_sieve() {
+    let limit = 200_000u64;
+    let primes = primes_up_to(limit);
+    let mut primes = primes.iter().peekable();
+    for n in 0..=limit {
+        let expected = primes.next_if_eq(&&n).is_some();
+        assert_eq!(n.baillie_psw_test(), expected, "u64 {n}");
+        assert_eq!((n as u128).baillie_psw_test(), expected, "u128 {n}");
+        assert_eq!(BigUint::from(n).baillie_psw_test(), expected, "BigUint {n}");
+    }
+}
+struct CountingRng {
+    inner: SplitMix64,
+    draws: usize,
+}
+impl Rng for CountingRng {
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+    fn below_u128(&mut self, bound: u128) -> u128 {
+        self.draws += 1;
+        self.inner.below_u128(bound)
+    }
+}
+#[test]
+fn random_miller_rabin_matches_deterministic() {
+    let mut rng = SplitMix64::new(7);
+    for n in 0..100_000u64 {
+        let expected = n.miller_rabin_test();
+        assert_eq!(
+            n.miller_rabin_test_random(&mut rng, 20),
+            expected,
+            "u64 {n}"
+        );
+        assert_eq!(
+            (n as i32).miller_rabin_test_random(&mut rng, 20),
+            expected,
+            "i32 {n}"
+        );
+        assert_eq!(
+            (n as u128).miller_rabin_test_random(&mut rng, 20),
+            expected,
+            "u128 {n}"
+        );
+    }
+    for n in [
+        3215031751u64,
+        341550071728321,
+        18446744073709551557,
+        18446744073709551559,
+    ] {
+        let expected = n.miller_rabin_test();
+        assert_eq!(
+            n.miller_rabin_test_random(&mut rng, 20),
+            expected,
+            "u64 {n}"
+        );
+    }
+    for n in [
+        (1u128 << 89) - 1,
+        (1 << 107) - 1,
+        ((1 << 61) - 1) * ((1 << 31) - 1),
+    ] {
+        let expected = n.miller_rabin_test();
+        assert_eq!(
+            n.miller_rabin_test_random(&mut rng, 20),
+            expected,
+            "u128 {n}"
+        );
+        assert_eq!(
+            n.miller_rabin_test_target_error(&mut rng, 40),
+            expected,
+            "u128 {n}"
+        );
+    }
+    for seed in 0..4 {
+        let mut first = SplitMix64::new(seed);
+        let mut second = SplitMix64::new(seed);
+        for n in 1_000_000..1_001_000u64 {
+            assert_eq!(
+                n.miller_rabin_test_random(&mut first, 1),
+                n.miller_rabin_test_random(&mut second, 1),
+                "{n}"
+            );
+        }
+    }
+}
+#[test]
+fn target_error_runs_half_as_many_rounds_as_bits() {
+    for bits in [1, 2, 7, 64, 80, 128] {
+        for prime in [1_000_000_007u64, 18446744073709551557] {
+            let mut rng = CountingRng {
+                inner: SplitMix64::new(bits as u64),
+                draws: 0,
+            };
+            assert!(prime.miller_rabin_test_target_error(&mut rng, bits));
+            assert_eq!(rng.draws, bits.div_ceil(2) as usize, "{prime} {bits}");
+        }
+        let mut rng = CountingRng {
+            inner: SplitMix64::new(bits as u64),
+            draws: 0,
+        };
+        assert!(((1u128 << 127) - 1).miller_rabin_test_target_error(&mut rng, bits));
+        assert_eq!(rng.draws, bits.div_ceil(2) as usize, "2^127 - 1 {bits}");
+    }
+}
+#[test]
+#[should_panic(expected = "bound must be positive")]
+fn below_rejects_zero_bound() {
+    SplitMix64::new(0).below(0);
+}
+fn lucas_pseudoprimes(limit: u64, test: fn(u64) -> bool) -> Vec<u64> {
+    let primes = primes_up_to(limit);
+    let mut primes = primes.iter().peekable();
+    (0..=limit)
+        .filter(|n| {
+            let prime = primes.next_if_eq(&n).is_some();
+            assert!(prime <= test(*n), "rejected prime {n}");
+            !prime && test(*n)
+        })
+        .collect()
+}
+// The extra-strong conditions straight from the sequence, with P the first value from 3 up
+// for which P^2 - 4 is a non-residue.
+fn is_extra_strong_lucas_pseudoprime(n: u64) -> bool {
+    let p = (3..).find(|p| (p * p - 4).jacobi(n) == Some(-1)).unwrap();
+    let squares = (n + 1).trailing_zeros();
+    let (u, mut v, _) = lucas_sequence(p, 1, (n + 1) >> squares, n).unwrap();
+    if u == 0 && (v == 2 || v == n - 2) {
+        return true;
+    }
+    (1..squares).any(|_| {
+        let zero = v == 0;
+        v = (v * v + n - 2) % n;
+        zero
+    })
+}
+#[test]
+fn lucas_tests_match_known_pseudoprimes() {
+    // OEIS A217255 and A217719.
+    assert_eq!(
+        lucas_pseudoprimes(100_000, u64::strong_lucas_test),
+        [
+            5459, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519, 75077, 97439
+        ]
+    );
+    // The trial division in front of the Lucas step already removes 989 = 23 * 43,
+    // 3239 = 41 * 79 and 31631 = 47 * 673.
+    assert_eq!(
+        lucas_pseudoprimes(100_000, u64::extra_strong_lucas_test),
+        [5777, 10877, 27971, 29681, 30739, 39059, 72389, 73919, 75077]
+    );
+    // Selfridge's method picks D = 5 and Q = -1 for all three, and (-1)^((n - 1) / 2) equals
+    // (-1/n) for every odd n, so the Euler condition on Q cannot reject them.
+    assert_eq!(
+        lucas_pseudoprimes(100_000, u64::frobenius_test),
+        [5777, 10877, 75077]
+    );
+    for n in [5459u64, 5777, 10877] {
+        assert!((n as u128).strong_lucas_test() && (n as i32).strong_lucas_test());
+        assert!(!n.baillie_psw_test());
+    }
+    assert!(!5459u64.frobenius_test());
+    for n in [989u64, 3239, 5777] {
+        assert!(is_extra_strong_lucas_pseudoprime(n), "{n}");
+    }
+    assert!((5777u128).extra_strong_lucas_test() && 5777i64.extra_strong_lucas_test());
+}
+#[test]
+fn lucas_tests_on_wide_inputs() {
+    for n in [(1u128 << 89) - 1, (1 << 127) - 1, u64::MAX as u128 - 58] {
+        assert!(n.strong_lucas_test() && n.extra_strong_lucas_test() && n.frobenius_test());
+    }
+    let composite = ((1u128 << 61) - 1) * ((1 << 61) - 1);
+    assert!(!composite.strong_lucas_test());
+    assert!(!composite.extra_strong_lucas_test());
+    assert!(!composite.frobenius_test());
+    assert!(!u128::MAX.frobenius_test());
+    assert!(!(-7i32).strong_lucas_test());
+}
+#[test]
+fn lucas_sequence_matches_recurrence() {
+    for modulus in (1..60u64).step_by(2) {
+        for (p, q) in [(1u64, modulus - 1), (3, 1), (4, 7), (0, 2)] {
+            let (mut u, mut v, mut q_k) = (0u64, 2 % modulus, 1 % modulus);
+            let (mut next_u, mut next_v) = (1 % modulus, p % modulus);
+            for k in 0..40u64 {
+                assert_eq!(
+                    lucas_sequence(p, q, k, modulus),
+                    Some((u, v, q_k)),
+                    "{p} {q} {k} {modulus}"
+                );
+                let step = |current: u64, next: u64| {
+                    (p * next + modulus * modulus - q % modulus * current) % modulus
+                };
+                (u, next_u) = (next_u, step(u, next_u));
+                (v, next_v) = (next_v, step(v, next_v));
+                q_k = q_k * (q % modulus) % modulus;
+            }
+        }
+    }
+    assert_eq!(lucas_sequence(1u64, 1, 10, 8), None);
+    assert_eq!(lucas_sequence(1u128, 1, 10, 0), None);
+}
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+#[test]
+fn witnesses_and_liars_partition_the_bases() {
+    assert!(2047u64.liars(1..2047).contains(&2));
+    assert!(!2047u64.miller_rabin_witnesses(1..2047).contains(&2));
+    assert_eq!(2047u64.liars(2..3), [2]);
+    assert_eq!(2047u64.liars(2049..2050), [2049]);
+    assert_eq!(9u32.liars(1..9), [1, 8]);
+    assert_eq!(13u8.miller_rabin_witnesses(1..13), Vec::<u8>::new());
+    assert_eq!(1u64.liars(0..10), Vec::<u64>::new());
+    for n in 3..300u64 {
+        let witnesses = n.miller_rabin_witnesses(1..n);
+        let liars = n.liars(1..n);
+        assert_eq!(witnesses.len() + liars.len(), n as usize - 1, "{n}");
+        assert_eq!(witnesses.is_empty(), n.miller_rabin_test(), "{n}");
+        // Rabin's bound: an odd composite n has at most phi(n) / 4 strong liars.
+        if n % 2 == 1 && !n.miller_rabin_test() {
+            assert!(4 * liars.len() <= n as usize, "{n}");
+        }
+    }
+    assert_eq!(
+        (2047i32).liars(1..2047),
+        2047u32
+            .liars(1..2047)
+            .iter()
+            .map(|&b| b as i32)
+            .collect::<Vec<_>>()
+    );
+}
+#[test]
+fn fermat_and_solovay_strassen_reject_zero_bases() {
+    for n in [7u64, 13, 561] {
+        assert!(!n.fermat_test(0) && !n.fermat_test(n) && !n.fermat_test(2 * n));
+        assert!(!n.solovay_strassen_test(0) && !n.solovay_strassen_test(n));
+    }
+    // Every small prime is one of the iterated bases, which must be skipped for it.
+    for n in primes_up_to(1000) {
+        assert!(
+            n.fermat_test_iter(15) && n.solovay_strassen_test_iter(15),
+            "{n}"
+        );
+        assert!(
+            (n as u128).fermat_test_iter(15) && (n as i32).solovay_strassen_test_iter(15),
+            "{n}"
+        );
+    }
+    assert!(47u8.fermat_test_iter(15) && 47i8.solovay_strassen_test_iter(15));
+}
+#[test]
+fn carmichael_numbers_fool_fermat_but_not_solovay_strassen() {
+    for n in [561u64, 1105, 1729, 2465, 2821, 6601, 8911] {
+        let coprime: Vec<u64> = (1..n).filter(|base| gcd(*base, n) == 1).collect();
+        assert!(coprime.iter().all(|&base| n.fermat_test(base)), "{n}");
+        let passing = coprime
+            .iter()
+            .filter(|&&base| n.solovay_strassen_test(base))
+            .count();
+        assert!(2 * passing <= coprime.len(), "{n}");
+        assert!(!n.solovay_strassen_test_iter(15), "{n}");
+    }
+    // 561 is an Euler pseudoprime to base 2 but not to base 5.
+    assert!(561u64.solovay_strassen_test(2));
+    assert!(!561u64.solovay_strassen_test(5));
+}
+// Evaluated at compile time, so a broken const path fails the build rather than the test.
+const P: u64 = next_prime_const(1 << 40);
+const _: () = assert!(P == 1_099_511_627_791 && is_prime_u64(P) && !is_prime_u64(P - 2));
+const _: () = assert!(is_prime_u64(u64::MAX - 58) && !is_prime_u64(u64::MAX));
+const _: () = assert!(is_prime_u32(u32::MAX - 4) && !is_prime_u32(3_215_031_751));
+const _: () = assert!(pow_mod_u64(2, 10, 1000) == 24 && pow_mod_u64(5, 0, 1) == 0);
+static SMALL_PRIMES: [u64; 16] = {
+    let mut primes = [0; 16];
+    let (mut index, mut candidate) = (0, 0);
+    while index < primes.len() {
+        candidate = next_prime_const(candidate + 1);
+        primes[index] = candidate;
+        index += 1;
+    }
+    primes
+};
+#[test]
+fn const_primality_matches_miller_rabin() {
+    assert_eq!(P, (1u64 << 40).next_prime().unwrap());
+    assert_eq!(SMALL_PRIMES.to_vec(), primes_up_to(53));
+    let mut rng = SplitMix64::new(24);
+    let samples: Vec<u64> = (0..100_000u64)
+        .chain((0..100_000).map(|_| rng.next_u64()))
+        .chain((0..20_000).map(|offset| u64::MAX - 2 * offset))
+        .chain([3_215_031_751, 3_825_123_056_546_413_051, u64::MAX - 58])
+        .collect();
+    for n in samples {
+        assert_eq!(is_prime_u64(n), n.miller_rabin_test(), "{n}");
+        if let Ok(small) = u32::try_from(n) {
+            assert_eq!(is_prime_u32(small), small.miller_rabin_test(), "{n}");
+        }
+        let modulus = n | 1;
+        let exponent = rng.next_u64() as u128 * rng.next_u64() as u128;
+        assert_eq!(
+            pow_mod_u64(n, exponent, modulus),
+            n.pow_mod(exponent, modulus),
+            "{n}"
+        );
+    }
+    for n in (0..1 << 20).step_by(997).chain([u64::MAX - 100]) {
+        assert_eq!(next_prime_const(n), n.next_prime().unwrap(), "{n}");
+    }
+}
+fn check_batch<T: Copy + num::ToPrimitive + MillerRabinTest + std::fmt::Debug>(values: &[T]) {
+    let expected: Vec<bool> = values
+        .iter()
+        .map(|value| value.miller_rabin_test())
+        .collect();
+    assert_eq!(is_prime_batch(values), expected, "{values:?}");
+    let bitset = is_prime_batch_bitset(values);
+    assert_eq!(bitset.len(), values.len().div_ceil(64));
+    // Bit i % 64 of word i / 64 holds value i, and the padding bits stay clear.
+    for (index, word) in bitset.iter().enumerate() {
+        for bit in 0..64 {
+            let prime = expected.get(64 * index + bit).copied().unwrap_or(false);
+            assert_eq!(word >> bit & 1 == 1, prime, "{index} {bit}");
+        }
+    }
+}
+#[test]
+fn batch_primality_matches_miller_rabin() {
+    let mut rng = SplitMix64::new(25);
+    let small: Vec<u64> = (0..10_000).collect();
+    for length in 0..=9 {
+        check_batch(&small[..length]);
+        check_batch(&small[small.len() - length..]);
+    }
+    check_batch(&small);
+    let wide: Vec<u64> = (0..1_001)
+        .map(|index| match index % 3 {
+            0 => rng.next_u64(),
+            1 => rng.next_u64() | 1,
+            _ => (rng.next_u64() >> 32).next_prime().unwrap(),
+        })
+        .chain([
+            3_215_031_751,
+            3_825_123_056_546_413_051,
+            u64::MAX - 58,
+            u64::MAX,
+        ])
+        .collect();
+    check_batch(&wide);
+    check_batch(&(-1_000i64..1_003).collect::<Vec<_>>());
+    check_batch(
+        &(-100i32..103)
+            .chain([i32::MIN, i32::MAX])
+            .collect::<Vec<_>>(),
+    );
+    check_batch(&(0..=255u8).collect::<Vec<_>>());
+    let above: Vec<u128> = (0..203)
+        .map(|index| match index % 4 {
+            0 => (rng.next_u64() as u128) << 64 | rng.next_u64() as u128,
+            1 => (1 << 64) + index,
+            2 => ((rng.next_u64() as u128) << 40).next_prime().unwrap(),
+            _ => rng.next_u64() as u128,
+        })
+        .chain([(1 << 64) + 13, u128::MAX - 158, 18_446_744_073_709_551_557])
+        .collect();
+    check_batch(&above);
+}
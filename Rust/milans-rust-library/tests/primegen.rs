@@ -0,0 +1,92 @@
+use milans_rust_core::random::SplitMix64;
+use milans_rust_library::math::factor::Factorize;
+use milans_rust_library::math::primality::MillerRabinTest;
+use milans_rust_library::math::primegen::RandomPrime;
+use num_bigint::BigUint;
+#[test]
+fn random_primes_have_exact_bit_length() {
+    let mut rng = SplitMix64::new(20);
+    for bits in 2..=64 {
+        for _ in 0..20 {
+            let prime = u64::random_prime(bits, &mut rng);
+            assert_eq!(u64::BITS - prime.leading_zeros(), bits, "{prime}");
+            assert!(prime.miller_rabin_test(), "{prime}");
+        }
+    }
+    for bits in (2..=128).chain([128; 20]) {
+        let prime = u128::random_prime(bits, &mut rng);
+        assert_eq!(u128::BITS - prime.leading_zeros(), bits, "{prime}");
+        assert!(prime.miller_rabin_test(), "{prime}");
+    }
+    for bits in (2..=256).step_by(7).chain([512]) {
+        let prime = BigUint::random_prime(bits, &mut rng);
+        assert_eq!(prime.bits(), bits as u64, "{prime}");
+        assert!((&prime).miller_rabin_test(), "{prime}");
+    }
+}
+#[test]
+fn safe_primes_have_prime_halves() {
+    let mut rng = SplitMix64::new(20);
+    for bits in 3..=64 {
+        let prime = u64::random_safe_prime(bits, &mut rng);
+        assert_eq!(u64::BITS - prime.leading_zeros(), bits, "{prime}");
+        assert!(prime.miller_rabin_test(), "{prime}");
+        assert!((prime / 2).miller_rabin_test(), "{prime}");
+    }
+    for bits in [3, 65, 100, 128] {
+        let prime = u128::random_safe_prime(bits, &mut rng);
+        assert_eq!(u128::BITS - prime.leading_zeros(), bits, "{prime}");
+        assert!(prime.miller_rabin_test() && (prime / 2).miller_rabin_test());
+    }
+    let prime = BigUint::random_safe_prime(256, &mut rng);
+    assert_eq!(prime.bits(), 256);
+    assert!((&prime).miller_rabin_test() && (&prime >> 1u32).miller_rabin_test());
+}
+// Largest prime factor of n, in bits.
+fn largest_factor_bits(n: u128) -> u32 {
+    let largest = n.factorize().primes().copied().max().unwrap();
+    u128::BITS - largest.leading_zeros()
+}
+// A strong prime p has a prime r | p - 1 with a prime t | r - 1, and a prime s | p + 1. The
+// generator draws r and s with bits / 2 - 8 bits and t with half of that.
+fn check_strong_prime(p: u128, bits: u32) {
+    let factor_bits = bits / 2 - 8;
+    assert_eq!(u128::BITS - p.leading_zeros(), bits, "{p}");
+    assert!(p.miller_rabin_test(), "{p}");
+    assert!(largest_factor_bits(p + 1) >= factor_bits, "{p}");
+    let r = (p - 1)
+        .factorize()
+        .primes()
+        .copied()
+        .filter(|r| u128::BITS - r.leading_zeros() >= factor_bits)
+        .find(|r| largest_factor_bits(r - 1) >= factor_bits / 2);
+    assert!(r.is_some(), "{p}");
+}
+#[test]
+fn strong_primes_have_large_factors() {
+    let mut rng = SplitMix64::new(20);
+    for bits in [32, 40, 48, 56, 64] {
+        for _ in 0..5 {
+            check_strong_prime(u64::random_strong_prime(bits, &mut rng) as u128, bits);
+        }
+    }
+    for bits in [80, 100, 127] {
+        check_strong_prime(u128::random_strong_prime(bits, &mut rng), bits);
+    }
+    let prime = BigUint::random_strong_prime(100, &mut rng);
+    check_strong_prime(u128::try_from(prime).unwrap(), 100);
+}
+#[test]
+fn same_seed_gives_same_primes() {
+    let generate = |seed: u64| {
+        let mut rng = SplitMix64::new(seed);
+        (
+            u64::random_prime(64, &mut rng),
+            u128::random_safe_prime(100, &mut rng),
+            u128::random_strong_prime(96, &mut rng),
+            BigUint::random_prime(300, &mut rng),
+        )
+    };
+    assert_eq!(generate(7), generate(7));
+    assert_ne!(generate(7), generate(8));
+}
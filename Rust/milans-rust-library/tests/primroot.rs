@@ -0,0 +1,79 @@
+use milans_rust_library::math::primroot::{
+    is_primitive_root, multiplicative_order, primitive_root, primitive_roots,
+};
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+fn totient(n: u64) -> u64 {
+    (1..=n).filter(|k| gcd(*k, n) == 1).count() as u64
+}
+fn brute_order(a: u64, n: u64) -> Option<u64> {
+    if gcd(a, n) != 1 {
+        return None;
+    }
+    let mut value = a % n;
+    let mut order = 1;
+    while value != 1 % n {
+        value = value * a % n;
+        order += 1;
+    }
+    Some(order)
+}
+#[test]
+fn multiplicative_order_matches_brute_force() {
+    for n in 1..400u64 {
+        for a in 0..n {
+            assert_eq!(multiplicative_order(a, n), brute_order(a, n), "{a} {n}");
+        }
+    }
+    assert_eq!(multiplicative_order(-1i32, 7), Some(2));
+    assert_eq!(multiplicative_order(2u64, 1), Some(1));
+}
+#[test]
+fn primitive_root_matches_brute_force() {
+    assert_eq!(primitive_root(1u64), Some(0));
+    assert_eq!(primitive_root(2u64), Some(1));
+    assert_eq!(primitive_root(4u64), Some(3));
+    assert_eq!(primitive_root(8u64), None);
+    assert_eq!(primitive_root(15u64), None);
+    for n in 2..600u64 {
+        let phi = totient(n);
+        let expected = (1..n).find(|g| brute_order(*g, n) == Some(phi));
+        assert_eq!(primitive_root(n), expected, "{n}");
+        for g in 0..n {
+            assert_eq!(is_primitive_root(g, n), brute_order(g, n) == Some(phi));
+        }
+    }
+    // Odd prime powers and their doubles, including a prime squared above 2^16.
+    for n in [
+        3u64.pow(10),
+        2 * 5u64.pow(8),
+        7u64.pow(7),
+        2 * 65537 * 65537,
+    ] {
+        let g = primitive_root(n).unwrap();
+        assert_eq!(multiplicative_order(g, n), Some(totient_of_prime_power(n)));
+    }
+}
+fn totient_of_prime_power(n: u64) -> u64 {
+    let odd = if n.is_multiple_of(2) { n / 2 } else { n };
+    let prime = (3..).find(|p| odd.is_multiple_of(*p)).unwrap();
+    odd / prime * (prime - 1)
+}
+#[test]
+fn primitive_roots_count_is_totient_of_totient() {
+    for n in 1..600u64 {
+        let mut roots: Vec<u64> = primitive_roots(n).collect();
+        roots.sort();
+        let phi = totient(n);
+        let expected: Vec<u64> = match primitive_root(n) {
+            None => Vec::new(),
+            Some(_) if n == 1 => vec![0],
+            Some(_) => (1..n).filter(|g| brute_order(*g, n) == Some(phi)).collect(),
+        };
+        assert_eq!(roots, expected, "{n}");
+        if !expected.is_empty() {
+            assert_eq!(roots.len() as u64, totient(phi), "{n}");
+        }
+    }
+}
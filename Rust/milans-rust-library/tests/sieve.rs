@@ -0,0 +1,60 @@
+use milans_rust_library::math::primality::MillerRabinTest;
+use milans_rust_library::math::sieve::{Primes, primes_in_range, primes_up_to};
+fn expected(range: std::ops::Range<u64>) -> Vec<u64> {
+    range.filter(|n| n.miller_rabin_test()).collect()
+}
+#[test]
+fn primes_up_to_matches_miller_rabin() {
+    for n in [
+        0, 1, 2, 3, 4, 5, 29, 30, 31, 1000, 983_039, 983_040, 983_041,
+    ] {
+        assert_eq!(primes_up_to(n), expected(0..n + 1), "{n}");
+    }
+    assert_eq!(primes_up_to(3_000_000), expected(0..3_000_001));
+}
+#[test]
+fn primes_in_range_matches_miller_rabin() {
+    let segment = 30 * (1 << 15);
+    let ranges = [
+        0..0,
+        7..7,
+        std::ops::Range { start: 10, end: 3 },
+        0..1,
+        1..3,
+        2..3,
+        5..6,
+        6..7,
+        31..37,
+        31..38,
+        segment - 17..segment + 23,
+        segment / 2..5 * segment / 2,
+        3 * segment + 11..3 * segment + 11,
+        1_000_003..1_000_033,
+        1_000_000_000_000..1_000_000_100_001,
+        (1 << 44) - 1_000_000..(1 << 44) + 1_000_000,
+        u64::MAX - 2_000..u64::MAX,
+    ];
+    for range in ranges {
+        assert_eq!(
+            primes_in_range(range.clone()),
+            expected(range.clone()),
+            "{range:?}"
+        );
+    }
+}
+#[test]
+fn primes_iterator_matches_miller_rabin() {
+    for start in [0, 1, 2, 3, 4, 6, 7, 8, 29, 31, 32, 983_041, 1_234_567_891] {
+        let primes: Vec<u64> = Primes::starting_at(start).take(3000).collect();
+        let last = *primes.last().unwrap();
+        assert_eq!(primes, expected(start..last + 1), "{start}");
+    }
+    let primes: Vec<u64> = Primes::starting_at(1_000_000_000_000_000_000)
+        .take(20)
+        .collect();
+    let last = *primes.last().unwrap();
+    assert_eq!(primes, expected(1_000_000_000_000_000_000..last + 1));
+    let primes: Vec<u64> = Primes::starting_at(u64::MAX - 1_000).collect();
+    assert_eq!(primes, expected(u64::MAX - 1_000..u64::MAX));
+    assert_eq!(Primes::new().nth(999_999), Some(15_485_863));
+}
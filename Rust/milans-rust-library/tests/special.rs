@@ -0,0 +1,92 @@
+use milans_rust_core::random::{Rng, SplitMix64};
+use milans_rust_library::math::primality::MillerRabinTest;
+use milans_rust_library::math::special::{
+    SpecialModulus, llr_test, lucas_lehmer, pepin_test, proth_test,
+};
+use num_bigint::BigUint;
+const MERSENNE_EXPONENTS: [u32; 15] =
+    [2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127, 521, 607, 1279];
+// OEIS A002235: n with 3 * 2^n - 1 prime.
+const RIESEL_THREE: [u32; 25] = [
+    1, 2, 3, 4, 6, 7, 11, 18, 34, 38, 43, 55, 64, 76, 94, 103, 143, 206, 216, 306, 324, 391, 458,
+    470, 827,
+];
+fn special_number(k: u64, n: u32, plus: bool) -> BigUint {
+    let product = BigUint::from(k) << n;
+    if plus { product + 1u32 } else { product - 1u32 }
+}
+#[test]
+fn lucas_lehmer_finds_mersenne_exponents() {
+    for p in 2..=1279 {
+        assert_eq!(lucas_lehmer(p), MERSENNE_EXPONENTS.contains(&p), "{p}");
+    }
+    assert!(!lucas_lehmer(11) && !lucas_lehmer(23));
+}
+#[test]
+fn pepin_separates_fermat_primes() {
+    for n in 0..=4 {
+        assert!(pepin_test(n), "F{n}");
+    }
+    for n in 5..=12 {
+        assert!(!pepin_test(n), "F{n}");
+    }
+}
+#[test]
+fn proth_matches_miller_rabin() {
+    assert!(proth_test(3, 2));
+    assert!(proth_test(3, 5) && proth_test(13, 8));
+    // 13 * 2^6 + 1 = 833 = 7^2 * 17.
+    assert!(!proth_test(13, 6));
+    for n in 1..=14 {
+        for k in (1..1u64 << n.min(10)).step_by(2) {
+            let number = special_number(k, n, true);
+            assert_eq!(proth_test(k, n), (&number).miller_rabin_test(), "{k} {n}");
+        }
+    }
+}
+#[test]
+fn llr_matches_miller_rabin() {
+    for n in 2..=900 {
+        assert_eq!(llr_test(3, n), RIESEL_THREE.contains(&n), "3 * 2^{n} - 1");
+    }
+    for n in 1..=14 {
+        for k in (1..1u64 << n.min(10)).step_by(2) {
+            let number = special_number(k, n, false);
+            assert_eq!(llr_test(k, n), (&number).miller_rabin_test(), "{k} {n}");
+        }
+    }
+}
+#[test]
+fn special_modulus_reduce_matches_remainder() {
+    let mut rng = SplitMix64::new(23);
+    for (k, n) in [
+        (1, 1),
+        (1, 7),
+        (1, 127),
+        (3, 2),
+        (5, 64),
+        (13, 100),
+        (u64::MAX, 3),
+        (12345, 200),
+    ] {
+        for plus in [false, true] {
+            let modulus = SpecialModulus::new(k, n, plus);
+            assert_eq!(*modulus.modulus(), special_number(k, n, plus));
+            let values = (0..200).map(|index| {
+                let words = 1 + index % (2 * (n as usize + 64) / 64 + 2);
+                (0..words).fold(BigUint::ZERO, |value, _| (value << 64u32) + rng.next_u64())
+            });
+            for x in values.chain([
+                BigUint::ZERO,
+                modulus.modulus().clone(),
+                modulus.modulus() * 2u32,
+            ]) {
+                assert_eq!(
+                    modulus.reduce(x.clone()),
+                    &x % modulus.modulus(),
+                    "{k} {n} {plus} {x}"
+                );
+            }
+        }
+    }
+}